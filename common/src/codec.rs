@@ -0,0 +1,268 @@
+use alkanes_support::id::AlkaneId;
+use anyhow::{Result, anyhow};
+
+/// Encoded size of an `AlkaneId`: block and tx as 16-byte little-endian words.
+pub const ALKANE_ID_LEN: usize = 32;
+
+/// Fixed binary encoding for values kept in contract storage.
+///
+/// Decoding never panics: short, long or otherwise malformed input is
+/// reported as an error so callers can surface it instead of trapping.
+pub trait Codec: Sized {
+    fn encode(&self) -> Vec<u8>;
+    fn decode(bytes: &[u8]) -> Result<Self>;
+}
+
+macro_rules! impl_int_codec {
+    ($($ty:ty),*) => {
+        $(
+            impl Codec for $ty {
+                fn encode(&self) -> Vec<u8> {
+                    self.to_le_bytes().to_vec()
+                }
+
+                fn decode(bytes: &[u8]) -> Result<Self> {
+                    let array = bytes.try_into().map_err(|_| {
+                        anyhow!(
+                            "expected {} bytes for {}, found {}",
+                            std::mem::size_of::<$ty>(),
+                            stringify!($ty),
+                            bytes.len()
+                        )
+                    })?;
+                    Ok(<$ty>::from_le_bytes(array))
+                }
+            }
+        )*
+    };
+}
+
+impl_int_codec!(u8, u32, u64, u128);
+
+impl Codec for bool {
+    fn encode(&self) -> Vec<u8> {
+        vec![*self as u8]
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self> {
+        match bytes {
+            [0] => Ok(false),
+            [1] => Ok(true),
+            _ => Err(anyhow!("invalid bool encoding")),
+        }
+    }
+}
+
+/// Raw bytes, stored as-is. An empty value reads back as unset.
+impl Codec for Vec<u8> {
+    fn encode(&self) -> Vec<u8> {
+        self.clone()
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self> {
+        Ok(bytes.to_vec())
+    }
+}
+
+impl Codec for AlkaneId {
+    fn encode(&self) -> Vec<u8> {
+        encode_alkane_id(self)
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self> {
+        decode_alkane_id(bytes)
+    }
+}
+
+pub fn encode_alkane_id(id: &AlkaneId) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(ALKANE_ID_LEN);
+    bytes.extend_from_slice(&id.block.to_le_bytes());
+    bytes.extend_from_slice(&id.tx.to_le_bytes());
+    bytes
+}
+
+pub fn decode_alkane_id(bytes: &[u8]) -> Result<AlkaneId> {
+    let mut reader = Reader::new(bytes);
+    let id = reader.read_alkane_id()?;
+    reader.finish()?;
+    Ok(id)
+}
+
+/// Appends fixed-width little-endian fields to a byte buffer.
+#[derive(Default)]
+pub struct Writer(Vec<u8>);
+
+impl Writer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn put_u8(&mut self, value: u8) -> &mut Self {
+        self.0.push(value);
+        self
+    }
+
+    pub fn put_u64(&mut self, value: u64) -> &mut Self {
+        self.0.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    pub fn put_u128(&mut self, value: u128) -> &mut Self {
+        self.0.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    pub fn put_bool(&mut self, value: bool) -> &mut Self {
+        self.put_u8(value as u8)
+    }
+
+    pub fn put_alkane_id(&mut self, id: &AlkaneId) -> &mut Self {
+        self.put_u128(id.block).put_u128(id.tx)
+    }
+
+    pub fn put_bytes(&mut self, bytes: &[u8]) -> &mut Self {
+        self.0.extend_from_slice(bytes);
+        self
+    }
+
+    pub fn into_inner(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+/// Reads fixed-width little-endian fields, failing instead of panicking when
+/// the input runs short.
+pub struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, offset: 0 }
+    }
+
+    pub fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self
+            .offset
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| {
+                anyhow!(
+                    "unexpected end of data: need {} bytes at offset {}, have {}",
+                    len,
+                    self.offset,
+                    self.bytes.len()
+                )
+            })?;
+        let slice = &self.bytes[self.offset..end];
+        self.offset = end;
+        Ok(slice)
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub fn read_u64(&mut self) -> Result<u64> {
+        u64::decode(self.take(8)?)
+    }
+
+    pub fn read_u128(&mut self) -> Result<u128> {
+        u128::decode(self.take(16)?)
+    }
+
+    pub fn read_bool(&mut self) -> Result<bool> {
+        bool::decode(self.take(1)?)
+    }
+
+    pub fn read_alkane_id(&mut self) -> Result<AlkaneId> {
+        Ok(AlkaneId {
+            block: self.read_u128()?,
+            tx: self.read_u128()?,
+        })
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.bytes.len() - self.offset
+    }
+
+    /// Errors if any bytes are left unread.
+    pub fn finish(&self) -> Result<()> {
+        if self.remaining() != 0 {
+            return Err(anyhow!("{} trailing bytes", self.remaining()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn integers_reject_wrong_length() {
+        assert!(u128::decode(&[0u8; 15]).is_err());
+        assert!(u128::decode(&[0u8; 17]).is_err());
+        assert!(u64::decode(&[0u8; 7]).is_err());
+        assert!(u64::decode(&[0u8; 9]).is_err());
+        assert!(u8::decode(&[]).is_err());
+        assert_eq!(u64::decode(&7u64.to_le_bytes()).unwrap(), 7);
+    }
+
+    #[test]
+    fn bool_rejects_invalid_byte() {
+        assert!(!bool::decode(&[0]).unwrap());
+        assert!(bool::decode(&[1]).unwrap());
+        assert!(bool::decode(&[2]).is_err());
+        assert!(bool::decode(&[]).is_err());
+        assert!(bool::decode(&[1, 0]).is_err());
+    }
+
+    #[test]
+    fn alkane_id_round_trips_and_rejects_trailing_bytes() {
+        let id = AlkaneId { block: 2, tx: 42 };
+        let mut bytes = id.encode();
+        assert_eq!(bytes.len(), ALKANE_ID_LEN);
+        assert_eq!(AlkaneId::decode(&bytes).unwrap(), id);
+
+        bytes.push(0);
+        assert!(AlkaneId::decode(&bytes).is_err());
+        assert!(AlkaneId::decode(&bytes[..ALKANE_ID_LEN - 1]).is_err());
+    }
+
+    #[test]
+    fn reader_reports_short_input() {
+        let mut reader = Reader::new(&[1, 2, 3]);
+        assert_eq!(reader.read_u8().unwrap(), 1);
+        assert!(reader.read_u64().is_err());
+        // A failed read does not consume anything
+        assert_eq!(reader.remaining(), 2);
+        assert!(reader.finish().is_err());
+    }
+
+    #[test]
+    fn reader_take_does_not_overflow() {
+        let mut reader = Reader::new(&[0u8; 4]);
+        reader.take(1).unwrap();
+        assert!(reader.take(usize::MAX).is_err());
+        assert_eq!(reader.take(3).unwrap(), &[0, 0, 0]);
+        reader.finish().unwrap();
+    }
+
+    #[test]
+    fn writer_and_reader_agree() {
+        let id = AlkaneId { block: 4, tx: 9 };
+        let mut writer = Writer::new();
+        writer.put_u8(1).put_u64(2).put_u128(3).put_bool(true).put_alkane_id(&id);
+        let bytes = writer.into_inner();
+
+        let mut reader = Reader::new(&bytes);
+        assert_eq!(reader.read_u8().unwrap(), 1);
+        assert_eq!(reader.read_u64().unwrap(), 2);
+        assert_eq!(reader.read_u128().unwrap(), 3);
+        assert!(reader.read_bool().unwrap());
+        assert_eq!(reader.read_alkane_id().unwrap(), id);
+        reader.finish().unwrap();
+    }
+}
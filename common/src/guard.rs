@@ -0,0 +1,60 @@
+use crate::storage::Cell;
+
+use anyhow::{Result, anyhow};
+
+/// A storage flag that rejects nested entry into a contract while one of
+/// its state-changing calls is still running.
+///
+/// If the guarded call fails, the runtime discards its storage writes, so
+/// the flag is never left set by an aborted call.
+pub struct ReentrancyLock(Cell<bool>);
+
+impl ReentrancyLock {
+    pub const fn new(keyword: &'static str) -> Self {
+        Self(Cell::new(keyword))
+    }
+
+    /// Takes the lock, returning a guard that releases it when dropped.
+    pub fn enter(&self) -> Result<LockGuard<'_>> {
+        if self.0.get()? {
+            return Err(anyhow!("Reentrant call rejected"));
+        }
+        self.0.set(&true);
+        Ok(LockGuard(self))
+    }
+
+    pub fn is_locked(&self) -> Result<bool> {
+        self.0.get()
+    }
+}
+
+pub struct LockGuard<'a>(&'a ReentrancyLock);
+
+impl Drop for LockGuard<'_> {
+    fn drop(&mut self) {
+        self.0.0.set(&false);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Kept in one test: the runtime's test storage is shared by every test
+    // thread in the binary.
+    #[test]
+    fn nested_enter_fails_until_guard_is_dropped() {
+        let lock = ReentrancyLock::new("/test/lock");
+        assert!(!lock.is_locked().unwrap());
+
+        let guard = lock.enter().unwrap();
+        assert!(lock.is_locked().unwrap());
+        let error = lock.enter().err().unwrap();
+        assert_eq!(error.to_string(), "Reentrant call rejected");
+
+        drop(guard);
+        assert!(!lock.is_locked().unwrap());
+        let _guard = lock.enter().unwrap();
+        assert!(lock.is_locked().unwrap());
+    }
+}
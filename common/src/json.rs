@@ -0,0 +1,136 @@
+use std::fmt::Write as _;
+
+/// Builds a JSON object field by field, escaping every string it is given.
+///
+/// Large token amounts should go through [`JsonObject::string`] as decimal
+/// text; JSON readers commonly parse numbers as doubles and lose precision
+/// past 2^53.
+pub struct JsonObject(String);
+
+impl Default for JsonObject {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JsonObject {
+    pub fn new() -> Self {
+        Self(String::from("{"))
+    }
+
+    fn key(&mut self, key: &str) -> &mut String {
+        if self.0.len() > 1 {
+            self.0.push(',');
+        }
+        push_string(&mut self.0, key);
+        self.0.push(':');
+        &mut self.0
+    }
+
+    pub fn string(&mut self, key: &str, value: &str) -> &mut Self {
+        push_string(self.key(key), value);
+        self
+    }
+
+    pub fn number(&mut self, key: &str, value: impl Into<u128>) -> &mut Self {
+        let value = value.into();
+        let _ = write!(self.key(key), "{}", value);
+        self
+    }
+
+    pub fn bool(&mut self, key: &str, value: bool) -> &mut Self {
+        self.key(key).push_str(if value { "true" } else { "false" });
+        self
+    }
+
+    pub fn object(&mut self, key: &str, value: JsonObject) -> &mut Self {
+        let value = value.finish();
+        self.key(key).push_str(&value);
+        self
+    }
+
+    pub fn array(&mut self, key: &str, items: Vec<JsonObject>) -> &mut Self {
+        let items: Vec<String> = items.into_iter().map(JsonObject::finish).collect();
+        let out = self.key(key);
+        out.push('[');
+        out.push_str(&items.join(","));
+        out.push(']');
+        self
+    }
+
+    pub fn finish(mut self) -> String {
+        self.0.push('}');
+        self.0
+    }
+}
+
+fn push_string(out: &mut String, value: &str) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn escaped(value: &str) -> String {
+        let mut out = String::new();
+        push_string(&mut out, value);
+        out
+    }
+
+    #[test]
+    fn push_string_escapes_quotes_and_backslashes() {
+        assert_eq!(escaped("plain"), r#""plain""#);
+        assert_eq!(escaped(r#"say "hi""#), r#""say \"hi\"""#);
+        assert_eq!(escaped(r"C:\vault"), r#""C:\\vault""#);
+        assert_eq!(escaped(r#"\""#), r#""\\\"""#);
+    }
+
+    #[test]
+    fn push_string_escapes_control_characters() {
+        assert_eq!(escaped("a\nb\rc\td"), r#""a\nb\rc\td""#);
+        assert_eq!(escaped("\u{0001}\u{001f}"), r#""\u0001\u001f""#);
+        // Only control characters are escaped; the rest passes through
+        assert_eq!(escaped("\u{007f}é✓"), "\"\u{007f}é✓\"");
+    }
+
+    #[test]
+    fn object_joins_fields_in_order() {
+        assert_eq!(JsonObject::new().finish(), "{}");
+
+        let mut inner = JsonObject::new();
+        inner.bool("on", false);
+        let mut item = JsonObject::new();
+        item.number("n", 1u64);
+
+        let mut object = JsonObject::new();
+        object
+            .string("name", "Vault \"1\"")
+            .number("amount", u128::MAX)
+            .bool("active", true)
+            .object("inner", inner)
+            .array("items", vec![item, JsonObject::new()])
+            .array("empty", Vec::new());
+        assert_eq!(
+            object.finish(),
+            concat!(
+                r#"{"name":"Vault \"1\"","amount":340282366920938463463374607431768211455,"#,
+                r#""active":true,"inner":{"on":false},"items":[{"n":1},{}],"empty":[]}"#
+            )
+        );
+    }
+}
@@ -0,0 +1,14 @@
+//! Storage, encoding and metadata helpers shared by the staking pool and
+//! vault contracts.
+
+pub mod codec;
+pub mod guard;
+pub mod json;
+pub mod metadata;
+pub mod storage;
+
+pub use codec::{Codec, Reader, Writer};
+pub use guard::ReentrancyLock;
+pub use json::JsonObject;
+pub use metadata::{Attribute, NftMetadata};
+pub use storage::{Cell, List, Map, Record, StringCell};
@@ -0,0 +1,122 @@
+use crate::json::JsonObject;
+
+/// Value of one metadata attribute.
+pub enum AttributeValue {
+    Text(String),
+    /// Small counts and heights, emitted as a JSON number with
+    /// `"display_type":"number"`.
+    Number(u64),
+    /// Token amounts, emitted as a decimal string so they keep full
+    /// precision.
+    Amount(u128),
+    Bool(bool),
+}
+
+pub struct Attribute {
+    pub trait_type: &'static str,
+    pub value: AttributeValue,
+}
+
+impl Attribute {
+    pub fn text(trait_type: &'static str, value: impl Into<String>) -> Self {
+        Self { trait_type, value: AttributeValue::Text(value.into()) }
+    }
+
+    pub fn number(trait_type: &'static str, value: u64) -> Self {
+        Self { trait_type, value: AttributeValue::Number(value) }
+    }
+
+    pub fn amount(trait_type: &'static str, value: u128) -> Self {
+        Self { trait_type, value: AttributeValue::Amount(value) }
+    }
+
+    pub fn bool(trait_type: &'static str, value: bool) -> Self {
+        Self { trait_type, value: AttributeValue::Bool(value) }
+    }
+}
+
+/// An NFT metadata document in the common `name` / `description` / `image` /
+/// `attributes` shape.
+pub struct NftMetadata {
+    pub name: String,
+    pub description: String,
+    /// Where the artwork is served, `alkane:<block>:<tx>` for the alkane whose
+    /// GetData (opcode 1000) returns it.
+    pub image: String,
+    pub attributes: Vec<Attribute>,
+}
+
+impl NftMetadata {
+    pub fn to_json(&self) -> String {
+        let attributes = self
+            .attributes
+            .iter()
+            .map(|attribute| {
+                let mut entry = JsonObject::new();
+                entry.string("trait_type", attribute.trait_type);
+                match &attribute.value {
+                    AttributeValue::Text(value) => entry.string("value", value),
+                    AttributeValue::Number(value) => {
+                        entry.string("display_type", "number").number("value", *value)
+                    }
+                    AttributeValue::Amount(value) => entry.string("value", &value.to_string()),
+                    AttributeValue::Bool(value) => entry.bool("value", *value),
+                };
+                entry
+            })
+            .collect();
+
+        let mut document = JsonObject::new();
+        document
+            .string("name", &self.name)
+            .string("description", &self.description)
+            .string("image", &self.image)
+            .array("attributes", attributes);
+        document.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_json_matches_expected_document() {
+        let metadata = NftMetadata {
+            name: "Pool \"A\" #3".to_string(),
+            description: "line one\nline two".to_string(),
+            image: "alkane:2:17".to_string(),
+            attributes: vec![
+                Attribute::text("state", "active"),
+                Attribute::number("stake_height", 840_000),
+                Attribute::amount("amount", u128::MAX),
+                Attribute::bool("auto_renew", true),
+            ],
+        };
+        assert_eq!(
+            metadata.to_json(),
+            concat!(
+                r#"{"name":"Pool \"A\" #3","description":"line one\nline two","#,
+                r#""image":"alkane:2:17","attributes":["#,
+                r#"{"trait_type":"state","value":"active"},"#,
+                r#"{"trait_type":"stake_height","display_type":"number","value":840000},"#,
+                r#"{"trait_type":"amount","value":"340282366920938463463374607431768211455"},"#,
+                r#"{"trait_type":"auto_renew","value":true}]}"#
+            )
+        );
+    }
+
+    #[test]
+    fn to_json_without_attributes() {
+        let metadata = NftMetadata {
+            name: String::new(),
+            description: String::new(),
+            image: String::new(),
+            attributes: Vec::new(),
+        };
+        assert_eq!(
+            metadata.to_json(),
+            r#"{"name":"","description":"","image":"","attributes":[]}"#
+        );
+    }
+}
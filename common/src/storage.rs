@@ -0,0 +1,292 @@
+use crate::codec::{Codec, Reader, Writer};
+
+use alkanes_runtime::storage::StoragePointer;
+use metashrew_support::index_pointer::KeyValuePointer;
+
+use anyhow::{Result, anyhow};
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+/// A structured value stored as `[version][body]`.
+///
+/// Decoding rejects unknown versions and trailing bytes, so a corrupt or
+/// foreign record surfaces as an error rather than as garbage fields.
+pub trait Record: Sized {
+    const VERSION: u8;
+
+    fn encode_body(&self, writer: &mut Writer);
+    fn decode_body(reader: &mut Reader) -> Result<Self>;
+}
+
+impl<T: Record> Codec for T {
+    fn encode(&self) -> Vec<u8> {
+        let mut writer = Writer::new();
+        writer.put_u8(T::VERSION);
+        self.encode_body(&mut writer);
+        writer.into_inner()
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self> {
+        let mut reader = Reader::new(bytes);
+        let version = reader.read_u8()?;
+        if version != T::VERSION {
+            return Err(anyhow!(
+                "unsupported record version {} (expected {})",
+                version,
+                T::VERSION
+            ));
+        }
+        let record = T::decode_body(&mut reader)?;
+        reader.finish()?;
+        Ok(record)
+    }
+}
+
+fn decode_at<T: Codec>(key: &str, bytes: &[u8]) -> Result<Option<T>> {
+    if bytes.is_empty() {
+        return Ok(None);
+    }
+    T::decode(bytes)
+        .map(Some)
+        .map_err(|e| anyhow!("corrupt storage at {}: {}", key, e))
+}
+
+/// A single typed value under a fixed storage keyword.
+///
+/// An unset cell reads as `None` (or `T::default()` through [`Cell::get`]),
+/// matching the zero value `get_value` returned for empty keys.
+pub struct Cell<T> {
+    keyword: &'static str,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T: Codec> Cell<T> {
+    pub const fn new(keyword: &'static str) -> Self {
+        Self {
+            keyword,
+            _marker: PhantomData,
+        }
+    }
+
+    fn pointer(&self) -> StoragePointer {
+        StoragePointer::from_keyword(self.keyword)
+    }
+
+    pub fn try_get(&self) -> Result<Option<T>> {
+        decode_at(self.keyword, &self.pointer().get())
+    }
+
+    pub fn require(&self) -> Result<T> {
+        self.try_get()?
+            .ok_or_else(|| anyhow!("{} is not set", self.keyword))
+    }
+
+    pub fn set(&self, value: &T) {
+        self.pointer().set(Arc::new(value.encode()));
+    }
+}
+
+impl<T: Codec + Default> Cell<T> {
+    pub fn get(&self) -> Result<T> {
+        Ok(self.try_get()?.unwrap_or_default())
+    }
+}
+
+/// Typed values keyed by an encoded key under a common prefix, e.g. one
+/// entry per vault `AlkaneId`.
+pub struct Map<K, V> {
+    prefix: &'static str,
+    _marker: PhantomData<fn(K) -> V>,
+}
+
+impl<K: Codec, V: Codec> Map<K, V> {
+    pub const fn new(prefix: &'static str) -> Self {
+        Self {
+            prefix,
+            _marker: PhantomData,
+        }
+    }
+
+    fn pointer(&self, key: &K) -> StoragePointer {
+        StoragePointer::from_keyword(self.prefix).select(&key.encode())
+    }
+
+    pub fn try_get(&self, key: &K) -> Result<Option<V>> {
+        decode_at(self.prefix, &self.pointer(key).get())
+    }
+
+    pub fn require(&self, key: &K) -> Result<V> {
+        self.try_get(key)?
+            .ok_or_else(|| anyhow!("no entry in {} for key", self.prefix))
+    }
+
+    pub fn set(&self, key: &K, value: &V) {
+        self.pointer(key).set(Arc::new(value.encode()));
+    }
+}
+
+impl<K: Codec, V: Codec + Default> Map<K, V> {
+    pub fn get(&self, key: &K) -> Result<V> {
+        Ok(self.try_get(key)?.unwrap_or_default())
+    }
+}
+
+/// A UTF-8 string with a maximum encoded length, checked on both write and
+/// read.
+pub struct StringCell {
+    keyword: &'static str,
+    max_len: usize,
+}
+
+impl StringCell {
+    pub const fn new(keyword: &'static str, max_len: usize) -> Self {
+        Self { keyword, max_len }
+    }
+
+    fn pointer(&self) -> StoragePointer {
+        StoragePointer::from_keyword(self.keyword)
+    }
+
+    pub fn try_get(&self) -> Result<Option<String>> {
+        let bytes = self.pointer().get();
+        if bytes.is_empty() {
+            return Ok(None);
+        }
+        if bytes.len() > self.max_len {
+            return Err(anyhow!(
+                "corrupt storage at {}: {} bytes exceeds limit of {}",
+                self.keyword,
+                bytes.len(),
+                self.max_len
+            ));
+        }
+        String::from_utf8(bytes.to_vec())
+            .map(Some)
+            .map_err(|e| anyhow!("corrupt storage at {}: {}", self.keyword, e))
+    }
+
+    pub fn get(&self) -> Result<String> {
+        Ok(self.try_get()?.unwrap_or_default())
+    }
+
+    pub fn set(&self, value: &str) -> Result<()> {
+        if value.len() > self.max_len {
+            return Err(anyhow!(
+                "{} is {} bytes, limit is {}",
+                self.keyword,
+                value.len(),
+                self.max_len
+            ));
+        }
+        self.pointer().set(Arc::new(value.as_bytes().to_vec()));
+        Ok(())
+    }
+}
+
+/// An append-only sequence of typed values, indexed from 0, with its length
+/// kept under `<keyword>/length`.
+pub struct List<T> {
+    keyword: &'static str,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T: Codec> List<T> {
+    pub const fn new(keyword: &'static str) -> Self {
+        Self {
+            keyword,
+            _marker: PhantomData,
+        }
+    }
+
+    fn length_pointer(&self) -> StoragePointer {
+        StoragePointer::from_keyword(self.keyword).select(&b"/length".to_vec())
+    }
+
+    fn item_pointer(&self, index: u128) -> StoragePointer {
+        StoragePointer::from_keyword(self.keyword).select(&index.encode())
+    }
+
+    pub fn len(&self) -> Result<u128> {
+        Ok(decode_at::<u128>(self.keyword, &self.length_pointer().get())?.unwrap_or_default())
+    }
+
+    pub fn is_empty(&self) -> Result<bool> {
+        Ok(self.len()? == 0)
+    }
+
+    pub fn get(&self, index: u128) -> Result<Option<T>> {
+        decode_at(self.keyword, &self.item_pointer(index).get())
+    }
+
+    /// Appends `value` and returns its index.
+    pub fn push(&self, value: &T) -> Result<u128> {
+        let index = self.len()?;
+        self.item_pointer(index).set(Arc::new(value.encode()));
+        self.length_pointer().set(Arc::new((index + 1).encode()));
+        Ok(index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Sample {
+        height: u64,
+        amount: u128,
+    }
+
+    impl Record for Sample {
+        const VERSION: u8 = 2;
+
+        fn encode_body(&self, writer: &mut Writer) {
+            writer.put_u64(self.height).put_u128(self.amount);
+        }
+
+        fn decode_body(reader: &mut Reader) -> Result<Self> {
+            Ok(Self {
+                height: reader.read_u64()?,
+                amount: reader.read_u128()?,
+            })
+        }
+    }
+
+    #[test]
+    fn record_round_trips() {
+        let sample = Sample { height: 840_000, amount: 5 };
+        let bytes = sample.encode();
+        assert_eq!(bytes[0], Sample::VERSION);
+        assert_eq!(Sample::decode(&bytes).unwrap(), sample);
+    }
+
+    #[test]
+    fn record_rejects_wrong_version() {
+        let mut bytes = Sample { height: 1, amount: 1 }.encode();
+        bytes[0] = 1;
+        assert!(Sample::decode(&bytes).is_err());
+    }
+
+    #[test]
+    fn record_rejects_trailing_and_missing_bytes() {
+        let mut bytes = Sample { height: 1, amount: 1 }.encode();
+        assert!(Sample::decode(&bytes[..bytes.len() - 1]).is_err());
+        bytes.push(0);
+        assert!(Sample::decode(&bytes).is_err());
+        assert!(Sample::decode(&[Sample::VERSION]).is_err());
+    }
+
+    #[test]
+    fn decode_at_treats_empty_as_unset_and_names_the_key() {
+        assert!(decode_at::<u128>("/k", &[]).unwrap().is_none());
+        let error = decode_at::<u128>("/k", &[1, 2]).unwrap_err();
+        assert!(error.to_string().contains("/k"));
+    }
+
+    #[test]
+    fn string_cell_rejects_values_over_the_limit() {
+        let cell = StringCell::new("/test/string-limit", 4);
+        let error = cell.set("toolong").unwrap_err();
+        assert!(error.to_string().contains("limit is 4"));
+    }
+}
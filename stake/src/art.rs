@@ -0,0 +1,62 @@
+use crate::state::{Position, PositionState};
+use crate::views::PoolStatus;
+
+/// What a vault's card shows, rendered by [`vault_svg`].
+pub struct VaultCard<'a> {
+    pub index: u128,
+    pub pool_name: &'a str,
+    pub position: &'a Position,
+    /// Settled rewards plus, while the position is active, its current
+    /// entitlement.
+    pub total_reward: u128,
+    pub phase: PoolStatus,
+}
+
+/// Renders a self-contained 400×400 SVG card for one vault.
+pub fn vault_svg(card: &VaultCard) -> String {
+    let lock_status = match card.position.state {
+        PositionState::Active => "Staked".to_string(),
+        PositionState::Unbonding => format!("Unbonding until {}", card.position.unlock_height),
+        PositionState::Exited | PositionState::Claimed => "Withdrawn".to_string(),
+        PositionState::Merged => "Merged".to_string(),
+    };
+
+    format!(
+        concat!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="400" height="400" viewBox="0 0 400 400">"#,
+            r##"<rect width="400" height="400" rx="24" fill="#141a2b"/>"##,
+            r##"<g font-family="monospace" fill="#e8ecf5">"##,
+            r#"<text x="32" y="64" font-size="22">{}</text>"#,
+            r##"<text x="32" y="100" font-size="18" fill="#8f9bb8">#{}</text>"##,
+            r##"<text x="32" y="168" font-size="14" fill="#8f9bb8">Staked</text>"##,
+            r#"<text x="32" y="192" font-size="20">{}</text>"#,
+            r##"<text x="32" y="232" font-size="14" fill="#8f9bb8">Rewards</text>"##,
+            r#"<text x="32" y="256" font-size="20">{}</text>"#,
+            r##"<text x="32" y="296" font-size="14" fill="#8f9bb8">Status</text>"##,
+            r#"<text x="32" y="320" font-size="16">{}</text>"#,
+            r#"<text x="32" y="368" font-size="14">{}</text>"#,
+            r#"</g></svg>"#
+        ),
+        escape_xml(card.pool_name),
+        card.index,
+        card.position.amount,
+        card.total_reward,
+        escape_xml(&lock_status),
+        card.phase.as_str(),
+    )
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -1,10 +1,11 @@
 use alkanes_runtime::{
-    declare_alkane, message::MessageDispatch, runtime::AlkaneResponder, storage::StoragePointer,
-    token::Token,
+    declare_alkane, message::MessageDispatch, runtime::AlkaneResponder, token::Token,
 };
 use metashrew_support::compat::to_arraybuffer_layout;
-use metashrew_support::index_pointer::KeyValuePointer;
 
+use alkanes_common::{
+    Attribute, Cell, JsonObject, List, Map, NftMetadata, ReentrancyLock, StringCell, Writer,
+};
 use alkanes_support::{
     cellpack::Cellpack,
     id::AlkaneId,
@@ -13,13 +14,87 @@ use alkanes_support::{
 };
 
 use anyhow::{Result, anyhow};
-use std::sync::Arc;
+
+mod art;
+mod state;
+mod views;
+#[cfg(test)]
+mod tests;
+use art::VaultCard;
+use state::{HaltState, PoolTotals, Position, PositionState, SyncRecord};
+use views::{
+    PoolConfig, PoolStatus, PositionView, RATE_PRECISION, RewardRate, Solvency, StakePreview, Status,
+    encode_totals,
+};
 
 const COLLECTION_SYMBOL: &str = "SLP";
 // Number of blocks in the claim window after the staking period ends.
 // 7 days on Alkanes: 144 blocks/day * 7 days = 1008 blocks.
 const CLAIM_WINDOW_BLOCKS: u64 = 144 * 7; // 1008
+// Most staking indices a single ListPositions call walks.
+const MAX_LIST_LIMIT: u128 = 100;
+// Longest cooldown SetCooldown accepts: 30 days of blocks.
+const MAX_COOLDOWN_BLOCKS: u64 = 144 * 30;
+// Upper bound for the stored collection name ("<token name> Staking").
+const MAX_COLLECTION_NAME_LEN: usize = 256;
+// Built-in collection image, served until the owner finalizes an upload.
 static COLLECTION_IMAGE: &[u8] = include_bytes!("assets/vault.png");
+const MAX_IMAGE_CHUNKS: u128 = 256;
+const MAX_CONTENT_TYPE_LEN: usize = 64;
+
+// Pool configuration, written once by `initialize`.
+const REWARD_TOKEN_ID: Cell<AlkaneId> = Cell::new("/reward_token_id");
+const STAKING_TOKEN_ID: Cell<AlkaneId> = Cell::new("/staking_token_id");
+const VAULT_TEMPLATE_ID: Cell<u128> = Cell::new("/vault_template_id");
+const MAX_TOTAL_STAKE: Cell<u128> = Cell::new("/max_total_stake");
+const START_HEIGHT: Cell<u64> = Cell::new("/start_height");
+const END_HEIGHT: Cell<u64> = Cell::new("/end_height");
+// Blocks between RequestUnstake and CompleteUnstake; 0 disables cooldown mode.
+const COOLDOWN_BLOCKS: Cell<u64> = Cell::new("/cooldown_blocks");
+const COLLECTION_NAME: StringCell = StringCell::new("/collection_name", MAX_COLLECTION_NAME_LEN);
+
+// Owner-controlled HaltState, stored as its u8 discriminant.
+const HALT_STATE: Cell<u8> = Cell::new("/halt_state");
+// Height of the current pause, and the blocks spent paused before it. The
+// claim deadline moves back by the total so a pause cannot eat into it.
+const PAUSED_AT: Cell<u64> = Cell::new("/paused_at");
+const PAUSED_BLOCKS: Cell<u64> = Cell::new("/paused_blocks");
+
+// Pools the owner allows positions to migrate into.
+const SUCCESSORS: Map<AlkaneId, bool> = Map::new("/successor/");
+
+// Successor that Roll stakes auto-renewing positions into.
+const RENEW_TARGET: Cell<AlkaneId> = Cell::new("/renew_target");
+// Vaults that have turned auto-renew on, each vault's latest queue index,
+// and how far Roll has got.
+const RENEW_QUEUE: List<AlkaneId> = List::new("/renew_queue");
+const RENEW_SLOT: Map<AlkaneId, u128> = Map::new("/renew_slot/");
+const ROLL_CURSOR: Cell<u128> = Cell::new("/roll_cursor");
+
+// Owner-uploaded collection image. Chunks live in two slots keyed by
+// `slot << 64 | chunk index`: uploads fill the draft slot and FinalizeImage
+// makes it live, so a half-finished upload never replaces the served image.
+const IMAGE_CHUNKS: Map<u128, Vec<u8>> = Map::new("/image/chunk/");
+const IMAGE_LIVE_SLOT: Cell<u128> = Cell::new("/image/live_slot");
+const IMAGE_LIVE_CHUNKS: Cell<u128> = Cell::new("/image/live_chunks");
+const IMAGE_LIVE_CONTENT_TYPE: StringCell =
+    StringCell::new("/image/live_content_type", MAX_CONTENT_TYPE_LEN);
+const IMAGE_DRAFT_CHUNKS: Cell<u128> = Cell::new("/image/draft_chunks");
+const IMAGE_DRAFT_CONTENT_TYPE: StringCell =
+    StringCell::new("/image/draft_content_type", MAX_CONTENT_TYPE_LEN);
+
+// Pool totals, updated as a single record.
+const TOTALS: Cell<PoolTotals> = Cell::new("/totals");
+// One record per vault, keyed by the vault's AlkaneId.
+const POSITIONS: Map<AlkaneId, Position> = Map::new("/position/");
+// Vault AlkaneId for each staking index, starting from 1.
+const VAULT_BY_INDEX: Map<u128, AlkaneId> = Map::new("/vault_by_index/");
+// Every Sync that added surplus reward tokens to the distribution.
+const SYNC_LOG: List<SyncRecord> = List::new("/sync_log");
+
+// Held while a state-changing call runs, so a vault (or anything it calls)
+// cannot re-enter the pool mid-update.
+const REENTRANCY_LOCK: ReentrancyLock = ReentrancyLock::new("/lock");
 
 #[derive(Default)]
 pub struct StakingPool(());
@@ -44,9 +119,132 @@ enum StakingPoolMessage {
     #[opcode(51)]
     Unstake,
 
+    #[opcode(52)]
+    PartialUnstake { amount: u128 },
+
+    #[opcode(53)]
+    IncreaseStake,
+
+    #[opcode(54)]
+    Merge,
+
+    #[opcode(55)]
+    Split { amount: u128 },
+
+    #[opcode(56)]
+    #[returns(Vec<u8>)]
+    BatchUnstake,
+
+    #[opcode(57)]
+    RequestUnstake,
+
+    #[opcode(58)]
+    CompleteUnstake,
+
+    #[opcode(59)]
+    Migrate { successor: AlkaneId },
+
+    #[opcode(60)]
+    SetAutoRenew { enabled: u128 },
+
+    #[opcode(61)]
+    #[returns(Vec<u8>)]
+    Roll { limit: u128 },
+
+    #[opcode(62)]
+    ClearEscaped { vault_id: AlkaneId },
+
+    #[opcode(70)]
+    #[returns(Vec<u8>)]
+    Sync,
+
     #[opcode(80)]
     Withdraw,
 
+    #[opcode(81)]
+    SetCooldown { blocks: u128 },
+
+    #[opcode(82)]
+    Pause,
+
+    #[opcode(83)]
+    Unpause,
+
+    #[opcode(84)]
+    Cancel,
+
+    #[opcode(85)]
+    SetSuccessor { pool: AlkaneId, allowed: u128 },
+
+    #[opcode(86)]
+    SetRenewTarget { pool: AlkaneId },
+
+    #[opcode(87)]
+    UploadImageChunk { index: u128, length: u128 },
+
+    #[opcode(88)]
+    SetImageContentType { length: u128 },
+
+    #[opcode(89)]
+    FinalizeImage,
+
+    #[opcode(200)]
+    #[returns(Vec<u8>)]
+    GetSolvency,
+
+    #[opcode(201)]
+    #[returns(Vec<u8>)]
+    GetHaltState,
+
+    #[opcode(202)]
+    #[returns(Vec<u8>)]
+    GetPosition { index: u128 },
+
+    #[opcode(203)]
+    #[returns(Vec<u8>)]
+    GetPositionByVault { vault_id: AlkaneId },
+
+    #[opcode(204)]
+    #[returns(Vec<u8>)]
+    ListPositions {
+        offset: u128,
+        limit: u128,
+        filter: u128,
+        min_amount: u128,
+    },
+
+    #[opcode(205)]
+    #[returns(String)]
+    GetPoolInfo,
+
+    #[opcode(206)]
+    #[returns(String)]
+    GetPositionInfo { vault_id: AlkaneId },
+
+    #[opcode(207)]
+    #[returns(Vec<u8>)]
+    GetPoolConfig,
+
+    #[opcode(208)]
+    #[returns(Vec<u8>)]
+    GetPoolTotals,
+
+    #[opcode(209)]
+    #[returns(Vec<u8>)]
+    GetStatus,
+
+    #[opcode(210)]
+    #[returns(Vec<u8>)]
+    PreviewStake { amount: u128 },
+
+    #[opcode(211)]
+    #[returns(Vec<u8>)]
+    GetRewardRate,
+
+    #[opcode(212)]
+    #[returns(Vec<u8>)]
+    GetSyncRecord { index: u128 },
+
     #[opcode(99)]
     #[returns(String)]
     GetName,
@@ -67,6 +265,10 @@ enum StakingPoolMessage {
     #[returns(Vec<u8>)]
     GetData { index: u128 },
 
+    #[opcode(1001)]
+    #[returns(String)]
+    GetContentType { index: u128 },
+
     #[opcode(1002)]
     #[returns(String)]
     GetAttributes,
@@ -74,7 +276,9 @@ enum StakingPoolMessage {
 
 impl Token for StakingPool {
     fn name(&self) -> String {
-        self.get_collection_name()
+        COLLECTION_NAME
+            .get()
+            .unwrap_or_else(|_| "Unknown SLP".to_string())
     }
 
     fn symbol(&self) -> String {
@@ -100,18 +304,18 @@ impl StakingPool {
             return Ok(response)
         }
 
-        self.set_reward_token_id(&reward_token_id);
-        self.set_staking_token_id(&staking_token_id);
-        self.set_vault_template_id(vault_template_id);
-        self.set_max_total_stake(max_total_stake);
-        self.start_height_pointer().set_value::<u64>(start_block as u64);
-        self.end_height_pointer().set_value::<u64>(end_block as u64);
-        
+        REWARD_TOKEN_ID.set(&reward_token_id);
+        STAKING_TOKEN_ID.set(&staking_token_id);
+        VAULT_TEMPLATE_ID.set(&vault_template_id);
+        MAX_TOTAL_STAKE.set(&max_total_stake);
+        START_HEIGHT.set(&(start_block as u64));
+        END_HEIGHT.set(&(end_block as u64));
+
         // Get staking token name and concatenate with "Staking"
         let staking_token_name = self.get_staking_token_name()?;
         let collection_name = format!("{} Staking", staking_token_name);
-        self.set_collection_name(&collection_name);
-        
+        COLLECTION_NAME.set(&collection_name)?;
+
         let mut total_reward_amount = 0u128;
         let mut invalid_alkanes = AlkaneTransferParcel::default();
         for alkane in &context.incoming_alkanes.0 {
@@ -121,13 +325,12 @@ impl StakingPool {
                 invalid_alkanes.0.push(alkane.clone());
             }
         }
-        self.set_total_reward_amount(total_reward_amount);
 
-        // Initialize total staking blocks and total staking amount to 0
-        self.set_staking_count(0);
-        self.set_total_stake_blocks(0);
-        self.set_total_stake_amount(0);
-        self.set_total_stake_weight(0);
+        // Staking count, blocks, amount and weight all start at 0
+        TOTALS.set(&PoolTotals {
+            reward_amount: total_reward_amount,
+            ..Default::default()
+        });
 
         let mut response = CallResponse::forward(&invalid_alkanes);
         response.alkanes.0.push(AlkaneTransfer {
@@ -138,8 +341,10 @@ impl StakingPool {
     }
 
     fn stake(&self) -> Result<CallResponse> {
+        let _lock = REENTRANCY_LOCK.enter()?;
         let context = self.context()?;
-        let staking_token_id = self.get_staking_token_id();
+        self.require_accepting_stake()?;
+        let staking_token_id = STAKING_TOKEN_ID.require()?;
 
         // Find the incoming staking asset
         let mut total_amount = 0u128;
@@ -154,424 +359,1246 @@ impl StakingPool {
             }
         }
 
+        let mut totals = TOTALS.get()?;
+
         // Validate staking parameters
-        self.validate_staking_parameters(total_amount)?;
+        self.validate_staking_parameters(&totals, total_amount)?;
 
         // Set staking index, starting from 1
-        let staking_index = self.get_next_staking_index();
-        self.set_staking_count(staking_index);
+        let staking_index = totals.staking_count.checked_add(1).unwrap_or(1);
+        totals.staking_count = staking_index;
 
-        // Call vault contract to create staking asset
-        let cellpack = Cellpack {
-            target: AlkaneId { block: 5, tx: self.get_vault_template_id() },
-            inputs: vec![0x0, staking_index],
-        };
-        let sequence = self.sequence();
-        let sub_response = self.call(&cellpack, &transfer, self.fuel())
-            .map_err(|e| anyhow!("Failed to create staking position: {}", e))?;
-        let vault_alkane = AlkaneId { block: 2, tx: sequence };
+        // The vault created below will take the next sequence number, so its
+        // position can be recorded before the pool calls out.
+        let vault_alkane = AlkaneId { block: 2, tx: self.sequence() };
 
         // Store staking data: staking block and staking amount
         let current_height = self.height() as u128;
-        let end_height = self.get_end_height() as u128;
-        
+        let end_height = END_HEIGHT.get()? as u128;
+
         // Calculate user's staking blocks (from staking start to mining end)
         let stake_blocks = end_height - current_height;
-        
-        // Store user's staking block height and staking blocks
-        self.set_stake_block(&vault_alkane, current_height);
-        self.set_stake_amount(&vault_alkane, total_amount);
-
-        // Store user's staking blocks (for weight calculation)
-        self.set_stake_blocks(&vault_alkane, stake_blocks);
-
-        // Store total staking blocks (sum of all users' staking blocks)
-        let total_stake_blocks = self.get_total_stake_blocks();
-        self.set_total_stake_blocks(total_stake_blocks + stake_blocks);
-
-        // Store total staking amount (sum of all users' staking amounts)
-        let total_stake_amount = self.get_total_stake_amount();
-        self.set_total_stake_amount(total_stake_amount + total_amount);
-        
-        // Update total staking weight (sum of user_amount * user_blocks)
+
+        // User weight = staking amount × staking blocks
         let user_weight = stake_blocks.saturating_mul(total_amount);
-        let total_weight = self.get_total_stake_weight();
-        self.set_total_stake_weight(total_weight.saturating_add(user_weight));
-        
+
+        // Store user's staking block height, amount, blocks and weight
+        POSITIONS.set(&vault_alkane, &Position {
+            stake_height: current_height as u64,
+            amount: total_amount,
+            stake_blocks,
+            weight: user_weight,
+            claimed_reward: 0,
+            reward_shortfall: 0,
+            state: PositionState::Active,
+            request_height: 0,
+            unlock_height: 0,
+            auto_renew: false,
+        });
+        VAULT_BY_INDEX.set(&staking_index, &vault_alkane);
+
+        // Update pool totals (sums of all users' blocks, amounts and weights)
+        totals.stake_blocks += stake_blocks;
+        totals.stake_amount += total_amount;
+        totals.stake_weight = totals.stake_weight.saturating_add(user_weight);
+        TOTALS.set(&totals);
+
+        // Call vault contract to create staking asset
+        let vault_nft = self.create_vault(staking_index, &vault_alkane, &transfer)?;
+
         let mut response = CallResponse::forward(&invalid_alkanes);
-        if sub_response.alkanes.0.is_empty() {
-            Err(anyhow!("Failed to create staking position"))
-        } else {
-            response.alkanes.0.push(sub_response.alkanes.0[0].clone());
-            Ok(response)
-        }
+        response.alkanes.0.push(vault_nft);
+        Ok(response)
     }
 
     fn unstake(&self) -> Result<CallResponse> {
+        let _lock = REENTRANCY_LOCK.enter()?;
+        let context = self.context()?;
+        self.require_not_paused()?;
+        self.require_no_cooldown()?;
+
+        let mut position = self.active_position(&context.caller)?;
+        let mut totals = TOTALS.get()?;
+
+        let reward_token = REWARD_TOKEN_ID.require()?;
+        let mut reward_balance = self.balance(&context.myself, &reward_token);
+        let reward = self.close_position(&mut position, &mut totals, &mut reward_balance)?;
+        TOTALS.set(&totals);
+        POSITIONS.set(&context.caller, &position);
+
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+        if reward > 0 {
+            response.alkanes.0.push(AlkaneTransfer {
+                id: reward_token,
+                value: reward,
+            });
+        }
+
+        response.data = STAKING_TOKEN_ID.require()?.try_into()?;
+        Ok(response)
+    }
+
+    // Called by a vault to release `amount` of its principal while keeping the
+    // position open. The withdrawn portion takes a proportional share of the
+    // position's weight and follows the same rules as a full Unstake: before
+    // the period ends it forfeits its rewards, inside the claim window it is
+    // paid its pro-rata reward.
+    fn partial_unstake(&self, amount: u128) -> Result<CallResponse> {
+        let _lock = REENTRANCY_LOCK.enter()?;
         let context = self.context()?;
+        self.require_not_paused()?;
+        self.require_no_cooldown()?;
 
-        let user_stake_amount = self.get_stake_amount(&context.caller);
-        let stake_block = self.get_stake_block(&context.caller);
-        if stake_block == 0 || user_stake_amount == 0 {
-            return Err(anyhow!("Caller is not a staker"));
+        let mut position = self.active_position(&context.caller)?;
+        if amount == 0 || amount >= position.amount {
+            return Err(anyhow!("Partial unstake amount must be below the staked amount"));
         }
+        let mut totals = TOTALS.get()?;
+        let withdrawn_weight = mul_div(position.weight, amount, position.amount);
 
         let mut response = CallResponse::forward(&context.incoming_alkanes);
-        let end_height = self.get_end_height();
+        let end_height = END_HEIGHT.get()?;
         let current_height = self.height();
 
-        // Staking period ended: allow reward claims within the claim window
         if current_height >= end_height {
-            // Check if within 7-day (1008 blocks) claim period
-            let claim_deadline = end_height + CLAIM_WINDOW_BLOCKS;
-            if current_height < claim_deadline {
-                // Single-claim model: Unstake burns the voucher; pay full entitlement once.
-                let total_reward_value = self.calc_reward(&context.caller);
-                if total_reward_value > 0 {
+            // Weights are frozen once the period ends; only the position shrinks.
+            if current_height < self.claim_deadline()? {
+                let reward_token = REWARD_TOKEN_ID.require()?;
+                let mut reward_balance = self.balance(&context.myself, &reward_token);
+                let reward = self.settle_reward(
+                    withdrawn_weight,
+                    &mut position,
+                    &mut totals,
+                    &mut reward_balance,
+                );
+                if reward > 0 {
                     response.alkanes.0.push(AlkaneTransfer {
-                        id: self.get_reward_token_id(),
-                        value: total_reward_value,
+                        id: reward_token,
+                        value: reward,
                     });
-
-                    // Record the claimed amount for reporting via get_attributes.
-                    self.set_user_claimed_reward(&context.caller, total_reward_value);
                 }
             }
-
-            // Note: We do not clear per-user staking data here because
-            // total distribution references historical weights for correctness.
+        } else {
+            // Early withdrawal of the portion: redistribute its weight
+            totals.stake_amount = totals.stake_amount.saturating_sub(amount);
+            totals.stake_weight = totals.stake_weight.saturating_sub(withdrawn_weight);
         }
-        // Staking not yet ended: early withdrawal without rewards
-        else {
-            // Deduct current staking amount from total, redistribute rewards to other stakers
-            let user_stake_blocks = self.get_stake_blocks(&context.caller);
-            let total_stake_blocks = self.get_total_stake_blocks();
-            self.set_total_stake_blocks(total_stake_blocks.saturating_sub(user_stake_blocks));
+        TOTALS.set(&totals);
 
-            let total_stake_amount = self.get_total_stake_amount();
-            self.set_total_stake_amount(total_stake_amount.saturating_sub(user_stake_amount));
+        position.amount -= amount;
+        position.weight -= withdrawn_weight;
+        POSITIONS.set(&context.caller, &position);
 
-            // Deduct user's weight from total staking weight
-            let user_weight = user_stake_blocks.saturating_mul(user_stake_amount);
-            let total_weight = self.get_total_stake_weight();
-            self.set_total_stake_weight(total_weight.saturating_sub(user_weight));
-        }
-
-        response.data = self.get_staking_token_id().try_into()?;
+        response.data = STAKING_TOKEN_ID.require()?.try_into()?;
         Ok(response)
     }
 
-    fn withdraw(&self) -> Result<CallResponse> {
-        self.only_owner()?;
+    // Adds staking tokens to an existing position. The caller authenticates
+    // by sending the vault NFT along with the tokens; the tokens move into the
+    // vault, the new amount earns weight for the blocks left in the period,
+    // and the NFT is handed back.
+    fn increase_stake(&self) -> Result<CallResponse> {
+        let _lock = REENTRANCY_LOCK.enter()?;
+        let context = self.context()?;
+        self.require_accepting_stake()?;
+        let staking_token_id = STAKING_TOKEN_ID.require()?;
 
-        let end_height = self.get_end_height();
-        let current_height = self.height();
-        // Owner can withdraw unclaimed rewards only after the claim window.
-        // Prior to that, users must have a chance to claim.
-        let claim_deadline = end_height + CLAIM_WINDOW_BLOCKS;
-        if current_height < claim_deadline {
-            return Err(anyhow!("Hold on, the user is claiming rewards."));
+        let mut added_amount = 0u128;
+        let mut transfer = AlkaneTransferParcel::default();
+        let mut vaults = Vec::new();
+        let mut invalid_alkanes = AlkaneTransferParcel::default();
+        for alkane in &context.incoming_alkanes.0 {
+            if alkane.id == staking_token_id {
+                transfer.0.push(alkane.clone());
+                added_amount += alkane.value;
+            } else if POSITIONS.try_get(&alkane.id)?.is_some() {
+                vaults.push(alkane.clone());
+            } else {
+                invalid_alkanes.0.push(alkane.clone());
+            }
+        }
+        let vault_nft = match vaults.as_slice() {
+            [vault_nft] => *vault_nft,
+            _ => return Err(anyhow!("Send exactly one vault of this pool")),
+        };
+        if added_amount == 0 {
+            return Err(anyhow!("No staking tokens supplied"));
         }
 
-        let context = self.context()?;
-        let mut response = CallResponse::forward(&context.incoming_alkanes);
+        let mut position = self.active_position(&vault_nft.id)?;
+        let mut totals = TOTALS.get()?;
+        self.validate_staking_parameters(&totals, added_amount)?;
 
-        let reward_token = self.get_reward_token_id();
-        // Transfer all remaining reward tokens in the pool back to the owner.
-        response.alkanes.0.push(AlkaneTransfer {
-            id: reward_token,
-            value: self.balance(&context.myself, &reward_token)
-        });
+        // The top-up only earns for the blocks left until the period ends
+        let remaining_blocks = END_HEIGHT.get()? as u128 - self.height() as u128;
+        let added_weight = remaining_blocks.saturating_mul(added_amount);
+
+        let previous_blocks = position.stake_blocks;
+        position.amount += added_amount;
+        position.weight = position.weight.saturating_add(added_weight);
+        position.stake_blocks = position.weight / position.amount;
+        POSITIONS.set(&vault_nft.id, &position);
+
+        totals.stake_blocks = totals.stake_blocks - previous_blocks + position.stake_blocks;
+        totals.stake_amount += added_amount;
+        totals.stake_weight = totals.stake_weight.saturating_add(added_weight);
+        TOTALS.set(&totals);
+
+        // Move the tokens into the vault
+        let cellpack = Cellpack {
+            target: vault_nft.id,
+            inputs: vec![40],
+        };
+        self.call(&cellpack, &transfer, self.fuel())
+            .map_err(|e| anyhow!("Failed to deposit into vault: {}", e))?;
 
+        let mut response = CallResponse::forward(&invalid_alkanes);
+        response.alkanes.0.push(vault_nft);
         Ok(response)
     }
 
-    fn calc_reward(&self, caller: &AlkaneId) -> u128 {
-        let user_stake_blocks = self.get_stake_blocks(caller);
-        let user_stake_amount = self.get_stake_amount(caller);
-        if user_stake_blocks == 0 || user_stake_amount == 0 {
-            return 0;
+    // Combines two or more vaults of this pool into the first one sent. Each
+    // merged position's principal, exact weight and settled rewards move to the
+    // survivor; the merged positions are marked Merged and their NFTs stay
+    // with the pool. Pool amount and weight totals are unchanged.
+    fn merge(&self) -> Result<CallResponse> {
+        let _lock = REENTRANCY_LOCK.enter()?;
+        let context = self.context()?;
+        self.require_not_paused()?;
+        let staking_token_id = STAKING_TOKEN_ID.require()?;
+
+        let mut vaults = Vec::new();
+        let mut invalid_alkanes = AlkaneTransferParcel::default();
+        for alkane in &context.incoming_alkanes.0 {
+            if POSITIONS.try_get(&alkane.id)?.is_some() && !vaults.contains(&alkane.id) {
+                vaults.push(alkane.id);
+            } else {
+                invalid_alkanes.0.push(alkane.clone());
+            }
+        }
+        if vaults.len() < 2 {
+            return Err(anyhow!("Merge needs at least two vaults of this pool"));
         }
 
-        // System total weight = sum of (amount × blocks) across all users
-        let total_weight = self.get_total_stake_weight();
+        let survivor_id = vaults[0];
+        let mut survivor = self.active_position(&survivor_id)?;
+        self.require_principal_in_vault(&survivor_id, &survivor)?;
+        let mut totals = TOTALS.get()?;
+        totals.stake_blocks -= survivor.stake_blocks;
+
+        let mut merged = Vec::with_capacity(vaults.len() - 1);
+        for vault_id in &vaults[1..] {
+            let mut position = self.active_position(vault_id)?;
+            self.require_principal_in_vault(vault_id, &position)?;
+            totals.stake_blocks -= position.stake_blocks;
+
+            survivor.stake_height = survivor.stake_height.min(position.stake_height);
+            survivor.amount += position.amount;
+            survivor.weight = survivor.weight.saturating_add(position.weight);
+            survivor.claimed_reward += position.claimed_reward;
+            survivor.reward_shortfall += position.reward_shortfall;
+
+            // Settled rewards now live on the survivor
+            position.claimed_reward = 0;
+            position.reward_shortfall = 0;
+            position.state = PositionState::Merged;
+            POSITIONS.set(vault_id, &position);
+            merged.push((*vault_id, position.amount));
+        }
+        survivor.stake_blocks = survivor.weight / survivor.amount;
+        POSITIONS.set(&survivor_id, &survivor);
 
-        // Calculate user weight: staking amount × staking blocks
-        let user_weight = user_stake_blocks * user_stake_amount;
+        totals.stake_blocks += survivor.stake_blocks;
+        TOTALS.set(&totals);
 
-        // Calculate user's deserved reward: distributed based on weight ratio
-        // Reward = total reward pool × (user weight / total weight)
-        let total_reward_amount = self.get_total_reward_amount();
-        match user_weight.checked_mul(total_reward_amount) {
-            Some(product) => {
-                if total_weight == 0 { 0 } else { product.checked_div(total_weight).unwrap_or(0) }
-            }
-            None => 0,
+        // Pull each merged vault's principal, then deposit it into the survivor
+        let mut transfer = AlkaneTransferParcel::default();
+        for (vault_id, amount) in merged {
+            self.release_from_vault(&vault_id, &staking_token_id, amount)?;
+            transfer.0.push(AlkaneTransfer {
+                id: staking_token_id,
+                value: amount,
+            });
         }
+        let cellpack = Cellpack {
+            target: survivor_id,
+            inputs: vec![40],
+        };
+        self.call(&cellpack, &transfer, self.fuel())
+            .map_err(|e| anyhow!("Failed to deposit into vault: {}", e))?;
+
+        let mut response = CallResponse::forward(&invalid_alkanes);
+        response.alkanes.0.push(AlkaneTransfer {
+            id: survivor_id,
+            value: 1u128,
+        });
+        Ok(response)
     }
 
-    fn only_owner(&self) -> Result<()> {
+    // Called by a vault, which sends `amount` of its principal along. A new
+    // vault is minted from the template with that principal and the same
+    // proportional share of the caller's weight, and registered under the
+    // next index. Its NFT goes back to the calling vault.
+    fn split(&self, amount: u128) -> Result<CallResponse> {
+        let _lock = REENTRANCY_LOCK.enter()?;
         let context = self.context()?;
+        self.require_not_paused()?;
+        let staking_token_id = STAKING_TOKEN_ID.require()?;
 
-        if context.incoming_alkanes.0.len() != 1 {
-            return Err(anyhow!("did not authenticate with only the collection token"));
+        let mut position = self.active_position(&context.caller)?;
+        if amount == 0 || amount >= position.amount {
+            return Err(anyhow!("Split amount must be below the staked amount"));
         }
 
-        let transfer = context.incoming_alkanes.0[0].clone();
-        if transfer.id != context.myself.clone() {
-            return Err(anyhow!("supplied alkane is not collection token"));
+        let mut transfer = AlkaneTransferParcel::default();
+        let mut invalid_alkanes = AlkaneTransferParcel::default();
+        for alkane in &context.incoming_alkanes.0 {
+            if alkane.id == staking_token_id {
+                transfer.0.push(alkane.clone());
+            } else {
+                invalid_alkanes.0.push(alkane.clone());
+            }
         }
-
-        if transfer.value < 1 {
-            return Err(anyhow!("less than 1 unit of collection token supplied to authenticate"));
+        let supplied: u128 = transfer.0.iter().map(|alkane| alkane.value).sum();
+        if supplied != amount {
+            return Err(anyhow!("Vault sent {} instead of {}", supplied, amount));
         }
 
-        Ok(())
+        let split_weight = mul_div(position.weight, amount, position.amount);
+        position.amount -= amount;
+        position.weight -= split_weight;
+        POSITIONS.set(&context.caller, &position);
+
+        let mut totals = TOTALS.get()?;
+        let split_index = totals.staking_count.checked_add(1).unwrap_or(1);
+        let split_alkane = AlkaneId { block: 2, tx: self.sequence() };
+        let split_position = Position {
+            stake_height: position.stake_height,
+            amount,
+            stake_blocks: position.stake_blocks,
+            weight: split_weight,
+            claimed_reward: 0,
+            reward_shortfall: 0,
+            state: PositionState::Active,
+            request_height: 0,
+            unlock_height: 0,
+            auto_renew: false,
+        };
+        POSITIONS.set(&split_alkane, &split_position);
+        VAULT_BY_INDEX.set(&split_index, &split_alkane);
+
+        // Amount and weight only move between positions
+        totals.staking_count = split_index;
+        totals.stake_blocks += split_position.stake_blocks;
+        TOTALS.set(&totals);
+
+        let vault_nft = self.create_vault(split_index, &split_alkane, &transfer)?;
+
+        let mut response = CallResponse::forward(&invalid_alkanes);
+        response.alkanes.0.push(vault_nft);
+        Ok(response)
     }
 
-    fn validate_staking_parameters(&self, stake_amount: u128) -> Result<()> {
-        if self.height() < self.get_start_height() {
-            return Err(anyhow!("Staking has not started yet"));
-        }
+    // Unstakes every vault NFT of this pool sent with the call. Each position is
+    // settled exactly as a single Unstake would settle it, its principal is
+    // pulled from the vault, and the combined principal and rewards are
+    // returned together. The NFTs stay with the pool.
+    //
+    // Response data, one 64-byte entry per vault in the order received:
+    // vault id (2 × u128 LE) | principal (u128 LE) | reward (u128 LE)
+    fn batch_unstake(&self) -> Result<CallResponse> {
+        let _lock = REENTRANCY_LOCK.enter()?;
+        let context = self.context()?;
+        self.require_not_paused()?;
+        let staking_token_id = STAKING_TOKEN_ID.require()?;
+        let reward_token = REWARD_TOKEN_ID.require()?;
 
-        // Reject staking when only 1 block away from deadline
-        if self.height() > self.get_end_height() - 2 {
-            return Err(anyhow!("Staking period has ended"));
+        let mut vaults = Vec::new();
+        let mut invalid_alkanes = AlkaneTransferParcel::default();
+        for alkane in &context.incoming_alkanes.0 {
+            if POSITIONS.try_get(&alkane.id)?.is_some() && !vaults.contains(&alkane.id) {
+                vaults.push(alkane.id);
+            } else {
+                invalid_alkanes.0.push(alkane.clone());
+            }
+        }
+        if vaults.is_empty() {
+            return Err(anyhow!("No vaults of this pool supplied"));
         }
 
-        let current_total_stake = self.get_total_stake_amount();
-        if current_total_stake + stake_amount > self.get_max_total_stake() {
-            return Err(anyhow!("Total staking amount exceeds maximum limit"));
+        // In cooldown mode only unbonding positions past their unlock height
+        // can be released; their rewards were settled by RequestUnstake.
+        let cooldown_blocks = COOLDOWN_BLOCKS.get()?;
+        let mut totals = TOTALS.get()?;
+        let mut reward_balance = self.balance(&context.myself, &reward_token);
+        let mut settled = Vec::with_capacity(vaults.len());
+        for vault_id in vaults {
+            let reward = if cooldown_blocks > 0 {
+                self.complete_position(&vault_id)?;
+                0
+            } else {
+                let mut position = self.active_position(&vault_id)?;
+                self.require_principal_in_vault(&vault_id, &position)?;
+                let reward =
+                    self.close_position(&mut position, &mut totals, &mut reward_balance)?;
+                POSITIONS.set(&vault_id, &position);
+                reward
+            };
+            settled.push((vault_id, self.balance(&vault_id, &staking_token_id), reward));
+        }
+        TOTALS.set(&totals);
+
+        let mut total_principal = 0u128;
+        let mut total_reward = 0u128;
+        let mut breakdown = Writer::new();
+        for (vault_id, principal, reward) in settled {
+            if principal > 0 {
+                self.release_from_vault(&vault_id, &staking_token_id, principal)?;
+            }
+            total_principal += principal;
+            total_reward += reward;
+            breakdown.put_alkane_id(&vault_id).put_u128(principal).put_u128(reward);
         }
 
-        Ok(())
+        let mut response = CallResponse::forward(&invalid_alkanes);
+        if total_principal > 0 {
+            response.alkanes.0.push(AlkaneTransfer {
+                id: staking_token_id,
+                value: total_principal,
+            });
+        }
+        if total_reward > 0 {
+            response.alkanes.0.push(AlkaneTransfer {
+                id: reward_token,
+                value: total_reward,
+            });
+        }
+        response.data = breakdown.into_inner();
+        Ok(response)
     }
 
-    fn reward_token_id_pointer(&self) -> StoragePointer {
-        StoragePointer::from_keyword("/reward_token_id")
-    }
+    // Cooldown mode, first step (called by a vault): settles the position as
+    // Unstake would, so it stops earning, and starts the cooldown. The
+    // principal stays in the vault until CompleteUnstake.
+    fn request_unstake(&self) -> Result<CallResponse> {
+        let _lock = REENTRANCY_LOCK.enter()?;
+        let context = self.context()?;
+        self.require_not_paused()?;
+        let cooldown_blocks = COOLDOWN_BLOCKS.get()?;
+        if cooldown_blocks == 0 {
+            return Err(anyhow!("Cooldown is not enabled, use Unstake"));
+        }
 
-    fn set_reward_token_id(&self, reward_token_id: &AlkaneId) {
-        let mut bytes = Vec::with_capacity(32);
-        bytes.extend_from_slice(&reward_token_id.block.to_le_bytes());
-        bytes.extend_from_slice(&reward_token_id.tx.to_le_bytes());
-        self.reward_token_id_pointer().set(Arc::new(bytes));
-    }
+        let mut position = self.active_position(&context.caller)?;
+        let mut totals = TOTALS.get()?;
+
+        let reward_token = REWARD_TOKEN_ID.require()?;
+        let mut reward_balance = self.balance(&context.myself, &reward_token);
+        let reward = self.close_position(&mut position, &mut totals, &mut reward_balance)?;
+        position.state = PositionState::Unbonding;
+        position.request_height = self.height();
+        position.unlock_height = position
+            .request_height
+            .checked_add(cooldown_blocks)
+            .ok_or_else(|| anyhow!("Unlock height overflows"))?;
+        TOTALS.set(&totals);
+        POSITIONS.set(&context.caller, &position);
 
-    fn get_reward_token_id(&self) -> AlkaneId {
-        let bytes = self.reward_token_id_pointer().get();
-        AlkaneId {
-            block: u128::from_le_bytes(bytes[0..16].try_into().unwrap()),
-            tx: u128::from_le_bytes(bytes[16..32].try_into().unwrap()),
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+        if reward > 0 {
+            response.alkanes.0.push(AlkaneTransfer {
+                id: reward_token,
+                value: reward,
+            });
         }
+        response.data = (position.unlock_height as u128).to_le_bytes().to_vec();
+        Ok(response)
     }
 
-    fn staking_token_id_pointer(&self) -> StoragePointer {
-        StoragePointer::from_keyword("/staking_token_id")
-    }
+    // Cooldown mode, second step (called by a vault): once the unlock height
+    // is reached the vault may release its principal.
+    fn complete_unstake(&self) -> Result<CallResponse> {
+        let _lock = REENTRANCY_LOCK.enter()?;
+        let context = self.context()?;
+        self.require_not_paused()?;
+        self.complete_position(&context.caller)?;
 
-    fn set_staking_token_id(&self, staking_token_id: &AlkaneId) {
-        let mut bytes = Vec::with_capacity(32);
-        bytes.extend_from_slice(&staking_token_id.block.to_le_bytes());
-        bytes.extend_from_slice(&staking_token_id.tx.to_le_bytes());
-        self.staking_token_id_pointer().set(Arc::new(bytes));
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+        response.data = STAKING_TOKEN_ID.require()?.try_into()?;
+        Ok(response)
     }
 
-    fn get_staking_token_id(&self) -> AlkaneId {
-        let bytes = self.staking_token_id_pointer().get();
-        AlkaneId {
-            block: u128::from_le_bytes(bytes[0..16].try_into().unwrap()),
-            tx: u128::from_le_bytes(bytes[16..32].try_into().unwrap()),
+    // Called by a vault, which sends its whole principal along. The position
+    // is settled as Unstake would settle it, and the principal is staked into
+    // `successor`, a pool the owner has whitelisted. The new vault NFT and
+    // any reward go back to the calling vault.
+    fn migrate(&self, successor: AlkaneId) -> Result<CallResponse> {
+        let _lock = REENTRANCY_LOCK.enter()?;
+        let context = self.context()?;
+        self.require_not_paused()?;
+        self.require_no_cooldown()?;
+        if !SUCCESSORS.get(&successor)? {
+            return Err(anyhow!("Successor pool is not allowed"));
         }
-    }
+        let staking_token_id = STAKING_TOKEN_ID.require()?;
 
-    fn collection_name_pointer(&self) -> StoragePointer {
-        StoragePointer::from_keyword("/collection_name")
-    }
-
-    fn set_collection_name(&self, name: &str) {
-        let name_bytes = name.as_bytes().to_vec();
-        self.collection_name_pointer().set(Arc::new(name_bytes));
-    }
+        let mut position = self.active_position(&context.caller)?;
+        let mut transfer = AlkaneTransferParcel::default();
+        let mut invalid_alkanes = AlkaneTransferParcel::default();
+        for alkane in &context.incoming_alkanes.0 {
+            if alkane.id == staking_token_id {
+                transfer.0.push(alkane.clone());
+            } else {
+                invalid_alkanes.0.push(alkane.clone());
+            }
+        }
+        let supplied: u128 = transfer.0.iter().map(|alkane| alkane.value).sum();
+        if supplied != position.amount {
+            return Err(anyhow!("Vault sent {} instead of {}", supplied, position.amount));
+        }
 
-    fn get_collection_name(&self) -> String {
-        let name_bytes = self.collection_name_pointer().get();
-        String::from_utf8(name_bytes.to_vec()).unwrap_or_else(|_| "Unknown SLP".to_string())
-    }
+        let mut totals = TOTALS.get()?;
+        let reward_token = REWARD_TOKEN_ID.require()?;
+        let mut reward_balance = self.balance(&context.myself, &reward_token);
+        let reward = self.close_position(&mut position, &mut totals, &mut reward_balance)?;
+        TOTALS.set(&totals);
+        POSITIONS.set(&context.caller, &position);
 
-    fn get_staking_token_name(&self) -> Result<String> {
         let cellpack = Cellpack {
-            target: self.get_staking_token_id(),
-            inputs: vec![99]
+            target: successor,
+            inputs: vec![50],
         };
+        let call_response = self.call(&cellpack, &transfer, self.fuel())
+            .map_err(|e| anyhow!("Failed to stake into successor: {}", e))?;
+        if !call_response.alkanes.0.iter().any(|alkane| alkane.id != staking_token_id) {
+            return Err(anyhow!("Successor did not return a vault"));
+        }
 
-        let call_response =
-            self.staticcall(&cellpack, &AlkaneTransferParcel::default(), self.fuel())?;
-        
-        let name_bytes = call_response.data;
-        String::from_utf8(name_bytes).map_err(|e| anyhow!("Failed to parse staking token name: {}", e))
-    }
-
-    fn total_stake_blocks_pointer(&self) -> StoragePointer {
-        StoragePointer::from_keyword("/total_stake_blocks")
+        let mut response = CallResponse::forward(&invalid_alkanes);
+        response.alkanes.0.extend(call_response.alkanes.0);
+        if reward > 0 {
+            response.alkanes.0.push(AlkaneTransfer {
+                id: reward_token,
+                value: reward,
+            });
+        }
+        Ok(response)
     }
 
-    fn get_total_stake_blocks(&self) -> u128 {
-        self.total_stake_blocks_pointer().get_value::<u128>()
-    }
+    // Called by a vault to turn auto-renew on (`enabled` non-zero) or off for
+    // its position.
+    fn set_auto_renew(&self, enabled: u128) -> Result<CallResponse> {
+        let _lock = REENTRANCY_LOCK.enter()?;
+        let context = self.context()?;
+        self.require_not_paused()?;
+        self.require_no_cooldown()?;
+
+        let mut position = self.active_position(&context.caller)?;
+        position.auto_renew = enabled != 0;
+        POSITIONS.set(&context.caller, &position);
+        // Queue the vault again if Roll has already passed its last entry
+        let queued = match RENEW_SLOT.try_get(&context.caller)? {
+            Some(slot) => slot >= ROLL_CURSOR.get()?,
+            None => false,
+        };
+        if position.auto_renew && !queued {
+            let slot = RENEW_QUEUE.push(&context.caller)?;
+            RENEW_SLOT.set(&context.caller, &slot);
+        }
+        Ok(CallResponse::forward(&context.incoming_alkanes))
+    }
+
+    // Permissionless, once the staking period has ended: settles up to
+    // `limit` auto-renewing positions and stakes their principal into the
+    // renew target. The new vault NFT is deposited into the old vault for the
+    // holder to Sweep out. Rewards are compounded into the new stake when the
+    // reward and staking tokens are the same, and deposited alongside the NFT
+    // otherwise. Later calls continue where the previous one stopped.
+    //
+    // A position whose vault no longer holds or will not release its
+    // principal is skipped with auto-renew turned off, so one bad vault
+    // cannot hold up the queue. If the renew target refuses the stake, the
+    // whole call fails and the cursor stays put, so a target that is not
+    // open yet, paused or full cannot cost holders their flag.
+    //
+    // Response data: positions rolled (u128 LE) | queue entries left (u128 LE)
+    fn roll(&self, limit: u128) -> Result<CallResponse> {
+        let _lock = REENTRANCY_LOCK.enter()?;
+        let context = self.context()?;
+        self.require_not_paused()?;
+        if self.height() < END_HEIGHT.get()? {
+            return Err(anyhow!("Staking period has not ended"));
+        }
+        let target = RENEW_TARGET.require()?;
+        if !SUCCESSORS.get(&target)? {
+            return Err(anyhow!("Renew target is no longer allowed"));
+        }
 
-    fn set_total_stake_blocks(&self, blocks: u128) {
-        self.total_stake_blocks_pointer().set_value::<u128>(blocks);
-    }
+        let queue_len = RENEW_QUEUE.len()?;
+        let start = ROLL_CURSOR.get()?;
+        let end = start.saturating_add(limit).min(queue_len);
+        ROLL_CURSOR.set(&end);
+
+        let mut reward_balance = self.balance(&context.myself, &REWARD_TOKEN_ID.require()?);
+        let mut rolled = 0u128;
+        for i in start..end {
+            let vault_id = RENEW_QUEUE.get(i)?.ok_or_else(|| anyhow!("Renew queue is corrupt"))?;
+            let mut position = match POSITIONS.try_get(&vault_id)? {
+                Some(position) if position.state == PositionState::Active && position.auto_renew => {
+                    position
+                }
+                _ => continue,
+            };
+            position.auto_renew = false;
+            if !self.principal_in_vault(&vault_id, &position)? {
+                POSITIONS.set(&vault_id, &position);
+                continue;
+            }
+            if self.roll_position(&vault_id, position, &target, &mut reward_balance)? {
+                rolled += 1;
+            }
+        }
 
-    fn stake_block_pointer(&self, alkane_id: &AlkaneId) -> StoragePointer {
-        StoragePointer::from_keyword(
-            format!("/stake_block/{}:{}", alkane_id.block, alkane_id.tx).as_str(),
-        )
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+        response.data = [rolled.to_le_bytes(), (queue_len - end).to_le_bytes()].concat();
+        Ok(response)
     }
 
-    fn get_stake_block(&self, alkane_id: &AlkaneId) -> u128 {
-        self.stake_block_pointer(alkane_id).get_value::<u128>()
-    }
+    // Rolls one position, whose principal the vault is known to hold, into
+    // `target`. The position and totals are written before any call; if the
+    // vault refuses to release the principal, they are put back as they
+    // were, with auto-renew already off in `position`, and false is
+    // returned. A failed stake into the target is returned as an error.
+    fn roll_position(
+        &self,
+        vault_id: &AlkaneId,
+        position: Position,
+        target: &AlkaneId,
+        reward_balance: &mut u128,
+    ) -> Result<bool> {
+        let staking_token_id = STAKING_TOKEN_ID.require()?;
+        let reward_token = REWARD_TOKEN_ID.require()?;
+        let compound = reward_token == staking_token_id;
+
+        let original_totals = TOTALS.get()?;
+        let original_balance = *reward_balance;
+        let mut totals = original_totals.clone();
+        let mut settled = position.clone();
+        let reward = self.close_position(&mut settled, &mut totals, reward_balance)?;
+        POSITIONS.set(vault_id, &settled);
+        TOTALS.set(&totals);
+
+        if self.release_from_vault(vault_id, &staking_token_id, position.amount).is_err() {
+            POSITIONS.set(vault_id, &position);
+            TOTALS.set(&original_totals);
+            *reward_balance = original_balance;
+            return Ok(false);
+        }
 
-    fn set_stake_block(&self, alkane_id: &AlkaneId, weight: u128) {
-        self.stake_block_pointer(alkane_id).set_value::<u128>(weight);
-    }
+        let mut stake = AlkaneTransferParcel::default();
+        stake.0.push(AlkaneTransfer {
+            id: staking_token_id,
+            value: if compound { position.amount + reward } else { position.amount },
+        });
+        let cellpack = Cellpack {
+            target: *target,
+            inputs: vec![50],
+        };
+        let call_response = self.call(&cellpack, &stake, self.fuel())
+            .map_err(|e| anyhow!("Failed to stake into renew target: {}", e))?;
+
+        let mut deposit = AlkaneTransferParcel::default();
+        deposit.0.extend(call_response.alkanes.0);
+        if !compound && reward > 0 {
+            deposit.0.push(AlkaneTransfer {
+                id: reward_token,
+                value: reward,
+            });
+        }
+        let cellpack = Cellpack {
+            target: *vault_id,
+            inputs: vec![40],
+        };
+        self.call(&cellpack, &deposit, self.fuel())
+            .map_err(|e| anyhow!("Failed to deposit into vault: {}", e))?;
+        Ok(true)
+    }
+
+    // Permissionless, before the staking period ends: an active position
+    // whose vault no longer holds its principal was emptied through the
+    // vault's EmergencyUnstake. It is settled as an early exit, so its
+    // weight stops taking a share of the rewards the remaining stakers earn.
+    // Allowed while paused, since a pause is what opens the exit.
+    fn clear_escaped(&self, vault_id: AlkaneId) -> Result<CallResponse> {
+        let _lock = REENTRANCY_LOCK.enter()?;
+        let context = self.context()?;
+        if self.height() >= END_HEIGHT.get()? {
+            return Err(anyhow!("Staking period has ended"));
+        }
 
-    fn stake_amount_pointer(&self, alkane_id: &AlkaneId) -> StoragePointer {
-        StoragePointer::from_keyword(
-            format!("/stake_amount/{}:{}", alkane_id.block, alkane_id.tx).as_str(),
-        )
-    }
+        let mut position = self.active_position(&vault_id)?;
+        if self.principal_in_vault(&vault_id, &position)? {
+            return Err(anyhow!("Vault still holds its principal"));
+        }
 
-    fn get_stake_amount(&self, alkane_id: &AlkaneId) -> u128 {
-        self.stake_amount_pointer(alkane_id).get_value::<u128>()
-    }
+        // Before the end close_position pays nothing, so no balance is needed
+        let mut totals = TOTALS.get()?;
+        let mut reward_balance = 0;
+        self.close_position(&mut position, &mut totals, &mut reward_balance)?;
+        position.auto_renew = false;
+        TOTALS.set(&totals);
+        POSITIONS.set(&vault_id, &position);
 
-    fn set_stake_amount(&self, alkane_id: &AlkaneId, amount: u128) {
-        self.stake_amount_pointer(alkane_id).set_value::<u128>(amount);
+        Ok(CallResponse::forward(&context.incoming_alkanes))
     }
 
-    fn stake_blocks_pointer(&self, alkane_id: &AlkaneId) -> StoragePointer {
-        StoragePointer::from_keyword(
-            format!("/stake_blocks/{}:{}", alkane_id.block, alkane_id.tx).as_str(),
-        )
-    }
+    // Permissionless: folds reward tokens the pool holds beyond its
+    // outstanding liability (direct transfers, leftovers) into the active
+    // distribution. Only allowed before the staking period ends, while no
+    // position has been paid against the current budget.
+    fn sync(&self) -> Result<CallResponse> {
+        let _lock = REENTRANCY_LOCK.enter()?;
+        let context = self.context()?;
+        self.require_accepting_stake()?;
+
+        let current_height = self.height();
+        if current_height >= END_HEIGHT.get()? {
+            return Err(anyhow!("Reward distribution is no longer active"));
+        }
+
+        let mut totals = TOTALS.get()?;
+        let balance = self.balance(&context.myself, &REWARD_TOKEN_ID.require()?);
+        let surplus = balance.saturating_sub(self.outstanding_liability(&totals)?);
+        if surplus == 0 {
+            return Err(anyhow!("No surplus reward tokens to sync"));
+        }
+
+        totals.reward_amount += surplus;
+        TOTALS.set(&totals);
+        SYNC_LOG.push(&SyncRecord {
+            amount: surplus,
+            height: current_height,
+        })?;
 
-    fn get_stake_blocks(&self, alkane_id: &AlkaneId) -> u128 {
-        self.stake_blocks_pointer(alkane_id).get_value::<u128>()
+        // Response data: amount added (u128 LE) | height (u128 LE)
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+        response.data = [surplus.to_le_bytes(), (current_height as u128).to_le_bytes()].concat();
+        Ok(response)
     }
 
-    fn set_stake_blocks(&self, alkane_id: &AlkaneId, blocks: u128) {
-        self.stake_blocks_pointer(alkane_id).set_value::<u128>(blocks);
+    // Owner-only, before staking starts: sets the cooldown between
+    // RequestUnstake and CompleteUnstake. Zero turns cooldown mode off.
+    fn set_cooldown(&self, blocks: u128) -> Result<CallResponse> {
+        let _lock = REENTRANCY_LOCK.enter()?;
+        self.only_owner()?;
+        if self.height() >= START_HEIGHT.get()? {
+            return Err(anyhow!("Cooldown can only be changed before staking starts"));
+        }
+        if blocks > MAX_COOLDOWN_BLOCKS as u128 {
+            return Err(anyhow!("Cooldown is limited to {} blocks", MAX_COOLDOWN_BLOCKS));
+        }
+        COOLDOWN_BLOCKS.set(&(blocks as u64));
+
+        let context = self.context()?;
+        Ok(CallResponse::forward(&context.incoming_alkanes))
     }
 
-    fn get_next_staking_index(&self) -> u128 {
-        self.get_staking_count().checked_add(1).unwrap_or(1)
+    // Owner-only: allows (`allowed` non-zero) or revokes `pool` as a
+    // migration target.
+    fn set_successor(&self, pool: AlkaneId, allowed: u128) -> Result<CallResponse> {
+        let _lock = REENTRANCY_LOCK.enter()?;
+        self.only_owner()?;
+        let context = self.context()?;
+        if pool == context.myself {
+            return Err(anyhow!("A pool cannot be its own successor"));
+        }
+        SUCCESSORS.set(&pool, &(allowed != 0));
+        Ok(CallResponse::forward(&context.incoming_alkanes))
     }
 
-    fn staking_count_pointer(&self) -> StoragePointer {
-        StoragePointer::from_keyword("/staking_count")
+    // Owner-only: sets the successor pool Roll stakes into. It must already
+    // be allowed through SetSuccessor.
+    fn set_renew_target(&self, pool: AlkaneId) -> Result<CallResponse> {
+        let _lock = REENTRANCY_LOCK.enter()?;
+        self.only_owner()?;
+        if !SUCCESSORS.get(&pool)? {
+            return Err(anyhow!("Renew target must be an allowed successor"));
+        }
+        RENEW_TARGET.set(&pool);
+
+        let context = self.context()?;
+        Ok(CallResponse::forward(&context.incoming_alkanes))
     }
 
-    pub fn get_staking_count(&self) -> u128 {
-        self.staking_count_pointer().get_value::<u128>()
+    // Owner-only: stores chunk `index` of the draft image. Chunks must be sent
+    // in order from 0; `length` bytes follow the fixed inputs, packed 16 to a
+    // u128 little-endian. Sending chunk 0 again restarts the draft.
+    fn upload_image_chunk(&self, index: u128, length: u128) -> Result<CallResponse> {
+        let _lock = REENTRANCY_LOCK.enter()?;
+        self.only_owner()?;
+        let context = self.context()?;
+
+        let draft_chunks = IMAGE_DRAFT_CHUNKS.get()?;
+        if index != 0 && index != draft_chunks {
+            return Err(anyhow!("Expected image chunk {}", draft_chunks));
+        }
+        if index >= MAX_IMAGE_CHUNKS {
+            return Err(anyhow!("Image is limited to {} chunks", MAX_IMAGE_CHUNKS));
+        }
+        let chunk = unpack_input_bytes(&context.inputs[3..], length)?;
+        if chunk.is_empty() {
+            return Err(anyhow!("Image chunk is empty"));
+        }
+
+        // Chunk 0 starts a new draft, which needs its own content type
+        if index == 0 {
+            IMAGE_DRAFT_CONTENT_TYPE.set("")?;
+        }
+        IMAGE_CHUNKS.set(&image_chunk_key(1 - IMAGE_LIVE_SLOT.get()?, index), &chunk);
+        IMAGE_DRAFT_CHUNKS.set(&(index + 1));
+        Ok(CallResponse::forward(&context.incoming_alkanes))
     }
 
-    fn set_staking_count(&self, count: u128) {
-        self.staking_count_pointer().set_value::<u128>(count)
+    // Owner-only: sets the content type of the draft image, sent like a chunk.
+    // Uploading chunk 0 clears it, so it is set after the draft is started.
+    fn set_image_content_type(&self, length: u128) -> Result<CallResponse> {
+        let _lock = REENTRANCY_LOCK.enter()?;
+        self.only_owner()?;
+        let context = self.context()?;
+
+        let bytes = unpack_input_bytes(&context.inputs[2..], length)?;
+        let content_type =
+            String::from_utf8(bytes).map_err(|_| anyhow!("Content type is not UTF-8"))?;
+        if content_type.is_empty() {
+            return Err(anyhow!("Content type is empty"));
+        }
+        IMAGE_DRAFT_CONTENT_TYPE.set(&content_type)?;
+        Ok(CallResponse::forward(&context.incoming_alkanes))
     }
 
-    pub fn get_start_height(&self) -> u64 {
-        self.start_height_pointer().get_value::<u64>()
+    // Owner-only: makes the draft image the one served by GetData.
+    fn finalize_image(&self) -> Result<CallResponse> {
+        let _lock = REENTRANCY_LOCK.enter()?;
+        self.only_owner()?;
+        let context = self.context()?;
+
+        let draft_chunks = IMAGE_DRAFT_CHUNKS.get()?;
+        if draft_chunks == 0 {
+            return Err(anyhow!("No image chunks uploaded"));
+        }
+        let content_type = IMAGE_DRAFT_CONTENT_TYPE
+            .try_get()?
+            .ok_or_else(|| anyhow!("Image content type is not set"))?;
+
+        IMAGE_LIVE_SLOT.set(&(1 - IMAGE_LIVE_SLOT.get()?));
+        IMAGE_LIVE_CHUNKS.set(&draft_chunks);
+        IMAGE_LIVE_CONTENT_TYPE.set(&content_type)?;
+        IMAGE_DRAFT_CHUNKS.set(&0);
+        IMAGE_DRAFT_CONTENT_TYPE.set("")?;
+        Ok(CallResponse::forward(&context.incoming_alkanes))
+    }
+
+    // Owner-only: stops every user operation until Unpause. Vaults can use
+    // their emergency exit while the pool is paused.
+    fn pause(&self) -> Result<CallResponse> {
+        let _lock = REENTRANCY_LOCK.enter()?;
+        self.only_owner()?;
+        if self.halt_state()? != HaltState::Running {
+            return Err(anyhow!("Pool is not running"));
+        }
+        HALT_STATE.set(&(HaltState::Paused as u8));
+        PAUSED_AT.set(&self.height());
+
+        let context = self.context()?;
+        Ok(CallResponse::forward(&context.incoming_alkanes))
     }
 
-    pub fn start_height_pointer(&self) -> StoragePointer {
-        StoragePointer::from_keyword("/start_height")
+    fn unpause(&self) -> Result<CallResponse> {
+        let _lock = REENTRANCY_LOCK.enter()?;
+        self.only_owner()?;
+        if self.halt_state()? != HaltState::Paused {
+            return Err(anyhow!("Pool is not paused"));
+        }
+        self.end_pause()?;
+        HALT_STATE.set(&(HaltState::Running as u8));
+
+        let context = self.context()?;
+        Ok(CallResponse::forward(&context.incoming_alkanes))
     }
 
-    pub fn get_end_height(&self) -> u64 {
-        self.end_height_pointer().get_value::<u64>()
+    // Owner-only and final: the pool stops accepting stake, while positions
+    // can still exit through the pool or the vault emergency exit.
+    fn cancel(&self) -> Result<CallResponse> {
+        let _lock = REENTRANCY_LOCK.enter()?;
+        self.only_owner()?;
+        if self.halt_state()? == HaltState::Cancelled {
+            return Err(anyhow!("Pool is already cancelled"));
+        }
+        if self.halt_state()? == HaltState::Paused {
+            self.end_pause()?;
+        }
+        HALT_STATE.set(&(HaltState::Cancelled as u8));
+
+        let context = self.context()?;
+        Ok(CallResponse::forward(&context.incoming_alkanes))
     }
 
-    pub fn end_height_pointer(&self) -> StoragePointer {
-        StoragePointer::from_keyword("/end_height")
+    fn withdraw(&self) -> Result<CallResponse> {
+        let _lock = REENTRANCY_LOCK.enter()?;
+        self.only_owner()?;
+        // A pause stops users from claiming, so it must stop the sweep too.
+        self.require_not_paused()?;
+
+        let current_height = self.height();
+        // Owner can withdraw unclaimed rewards only after the claim window.
+        // Prior to that, users must have a chance to claim.
+        let claim_deadline = self.claim_deadline()?;
+        if current_height < claim_deadline {
+            return Err(anyhow!("Hold on, the user is claiming rewards."));
+        }
+
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+
+        let reward_token = REWARD_TOKEN_ID.require()?;
+        // Transfer all remaining reward tokens in the pool back to the owner.
+        response.alkanes.0.push(AlkaneTransfer {
+            id: reward_token,
+            value: self.balance(&context.myself, &reward_token)
+        });
+
+        Ok(response)
     }
 
-    fn vault_template_id_pointer(&self) -> StoragePointer {
-        StoragePointer::from_keyword("/vault_template_id")
+    fn calc_reward(&self, user_weight: u128, totals: &PoolTotals) -> u128 {
+        if user_weight == 0 {
+            return 0;
+        }
+
+        // System total weight = sum of (amount × blocks) across all users
+        let total_weight = totals.stake_weight;
+
+        // Calculate user's deserved reward: distributed based on weight ratio
+        // Reward = total reward pool × (user weight / total weight)
+        match user_weight.checked_mul(totals.reward_amount) {
+            Some(product) => {
+                if total_weight == 0 { 0 } else { product.checked_div(total_weight).unwrap_or(0) }
+            }
+            None => 0,
+        }
     }
 
-    fn set_vault_template_id(&self, vault_template_id: u128) {
-        let mut p = self.vault_template_id_pointer();
-        p.set_value::<u128>(vault_template_id);
+    // Settles an active position in full and returns the reward to pay.
+    // Inside the claim window the position's entitlement is paid; before the
+    // period ends it exits early and its weight is redistributed.
+    fn close_position(
+        &self,
+        position: &mut Position,
+        totals: &mut PoolTotals,
+        reward_balance: &mut u128,
+    ) -> Result<u128> {
+        let end_height = END_HEIGHT.get()?;
+        let current_height = self.height();
+        let mut reward = 0;
+
+        // Staking period ended: allow reward claims within the claim window
+        if current_height >= end_height {
+            // Check if within 7-day (1008 blocks) claim period, extended by
+            // any time spent paused
+            let claim_deadline = self.claim_deadline()?;
+            if current_height < claim_deadline {
+                // Single-claim model: Unstake burns the voucher; pay full entitlement once.
+                reward = self.settle_reward(position.weight, position, totals, reward_balance);
+            }
+            position.state = PositionState::Claimed;
+
+            // Note: We do not clear per-user staking data here because
+            // total distribution references historical weights for correctness.
+        }
+        // Staking not yet ended: early withdrawal without rewards
+        else {
+            // Deduct current staking amount from total, redistribute rewards to other stakers
+            totals.stake_blocks = totals.stake_blocks.saturating_sub(position.stake_blocks);
+            totals.stake_amount = totals.stake_amount.saturating_sub(position.amount);
+
+            // Deduct user's weight from total staking weight
+            totals.stake_weight = totals.stake_weight.saturating_sub(position.weight);
+            position.state = PositionState::Exited;
+        }
+        Ok(reward)
     }
 
-    fn get_vault_template_id(&self) -> u128 {
-        self.vault_template_id_pointer().get_value::<u128>()
+    // Computes the reward earned by `weight` of a position and records what
+    // is paid (and any shortfall) on the position and totals. `reward_balance`
+    // is the pool's reward balance not yet committed to earlier payouts in the
+    // same call, and is reduced by the amount paid. The caller checks the
+    // claim window, transfers the reward and persists the records.
+    fn settle_reward(
+        &self,
+        weight: u128,
+        position: &mut Position,
+        totals: &mut PoolTotals,
+        reward_balance: &mut u128,
+    ) -> u128 {
+        let entitlement = self.calc_reward(weight, totals);
+        let paid = Self::payable_reward(entitlement, *reward_balance, totals.unsettled_reward());
+        *reward_balance -= paid;
+
+        // Record the claimed amount for reporting via get_attributes,
+        // and whatever a short balance left unpaid.
+        let shortfall = entitlement - paid;
+        position.claimed_reward += paid;
+        position.reward_shortfall += shortfall;
+        totals.claimed_reward += paid;
+        totals.reward_shortfall += shortfall;
+        paid
+    }
+
+    // Mints vault `index` from the template, moving `transfer` into it, and
+    // returns its NFT. `vault_id` is the id predicted from `self.sequence()`,
+    // under which the caller has already recorded the position.
+    fn create_vault(
+        &self,
+        index: u128,
+        vault_id: &AlkaneId,
+        transfer: &AlkaneTransferParcel,
+    ) -> Result<AlkaneTransfer> {
+        let staking_token_id = STAKING_TOKEN_ID.require()?;
+        // Past this height nothing is owed to the position, so the vault may
+        // release its principal without the pool.
+        let cooldown_blocks = COOLDOWN_BLOCKS.get()?;
+        let safety_height = END_HEIGHT
+            .get()?
+            .checked_add(CLAIM_WINDOW_BLOCKS)
+            .and_then(|height| height.checked_add(cooldown_blocks))
+            .ok_or_else(|| anyhow!("Safety height overflows"))?;
+        let cellpack = Cellpack {
+            target: AlkaneId { block: 5, tx: VAULT_TEMPLATE_ID.get()? },
+            inputs: vec![
+                0x0,
+                index,
+                staking_token_id.block,
+                staking_token_id.tx,
+                safety_height as u128,
+            ],
+        };
+        let sub_response = self.call(&cellpack, transfer, self.fuel())
+            .map_err(|e| anyhow!("Failed to create staking position: {}", e))?;
+
+        sub_response
+            .alkanes
+            .0
+            .iter()
+            .find(|alkane| alkane.id == *vault_id)
+            .copied()
+            .ok_or_else(|| anyhow!("Failed to create staking position"))
+    }
+
+    // A vault can hand its principal straight to the holder through
+    // EmergencyUnstake without telling the pool. Its position stays Active
+    // with its full amount and weight, but it is dead weight: the principal
+    // is gone and the NFT is locked in the vault. Code that moves a
+    // position's principal checks the vault's real balance first, and
+    // ClearEscaped takes such a position out of the totals before the end.
+    fn principal_in_vault(&self, vault: &AlkaneId, position: &Position) -> Result<bool> {
+        let staking_token_id = STAKING_TOKEN_ID.require()?;
+        Ok(self.balance(vault, &staking_token_id) >= position.amount)
+    }
+
+    fn require_principal_in_vault(&self, vault: &AlkaneId, position: &Position) -> Result<()> {
+        if !self.principal_in_vault(vault, position)? {
+            return Err(anyhow!("Vault {}:{} no longer holds its principal", vault.block, vault.tx));
+        }
+        Ok(())
     }
 
-    fn max_total_stake_pointer(&self) -> StoragePointer {
-        StoragePointer::from_keyword("/max_total_stake")
+    // Asks a vault to send `amount` of `token` to the pool.
+    fn release_from_vault(&self, vault: &AlkaneId, token: &AlkaneId, amount: u128) -> Result<()> {
+        let held = self.balance(vault, token);
+        if held < amount {
+            return Err(anyhow!("Vault holds {} instead of {}", held, amount));
+        }
+        let cellpack = Cellpack {
+            target: *vault,
+            inputs: vec![41, token.block, token.tx, amount],
+        };
+        let call_response = self.call(&cellpack, &AlkaneTransferParcel::default(), self.fuel())
+            .map_err(|e| anyhow!("Failed to release from vault: {}", e))?;
+        let released: u128 = call_response
+            .alkanes
+            .0
+            .iter()
+            .filter(|alkane| alkane.id == *token)
+            .map(|alkane| alkane.value)
+            .sum();
+        if released != amount {
+            return Err(anyhow!("Vault released {} instead of {}", released, amount));
+        }
+        Ok(())
     }
 
-    fn set_max_total_stake(&self, max_total_stake: u128) {
-        self.max_total_stake_pointer().set_value::<u128>(max_total_stake)
+    // Marks an unbonding position whose cooldown has passed as finished.
+    fn complete_position(&self, vault: &AlkaneId) -> Result<()> {
+        let mut position = POSITIONS
+            .try_get(vault)?
+            .ok_or_else(|| anyhow!("Caller is not a staker"))?;
+        if position.state != PositionState::Unbonding {
+            return Err(anyhow!("No unstake has been requested"));
+        }
+        if self.height() < position.unlock_height {
+            return Err(anyhow!("Cooldown ends at block {}", position.unlock_height));
+        }
+
+        // RequestUnstake settled the position as an early exit or a claim
+        // depending on when it was made.
+        position.state = if position.request_height < END_HEIGHT.get()? {
+            PositionState::Exited
+        } else {
+            PositionState::Claimed
+        };
+        POSITIONS.set(vault, &position);
+        Ok(())
     }
 
-    fn get_max_total_stake(&self) -> u128 {
-        self.max_total_stake_pointer().get_value::<u128>()
+    fn halt_state(&self) -> Result<HaltState> {
+        HALT_STATE.get()?.try_into()
     }
 
-    fn total_stake_amount_pointer(&self) -> StoragePointer {
-        StoragePointer::from_keyword("/total_stake_amount")
+    // End of the claim window: CLAIM_WINDOW_BLOCKS after the staking period,
+    // pushed back by every block the pool has spent paused.
+    fn claim_deadline(&self) -> Result<u64> {
+        let mut paused_blocks = PAUSED_BLOCKS.get()?;
+        if self.halt_state()? == HaltState::Paused {
+            paused_blocks += self.height().saturating_sub(PAUSED_AT.get()?);
+        }
+        Ok(END_HEIGHT.get()? + CLAIM_WINDOW_BLOCKS + paused_blocks)
     }
 
-    fn get_total_stake_amount(&self) -> u128 {
-        self.total_stake_amount_pointer().get_value::<u128>()
+    fn end_pause(&self) -> Result<()> {
+        let paused_for = self.height().saturating_sub(PAUSED_AT.get()?);
+        PAUSED_BLOCKS.set(&(PAUSED_BLOCKS.get()? + paused_for));
+        Ok(())
     }
 
-    fn set_total_stake_amount(&self, total_stake: u128) {
-        self.total_stake_amount_pointer().set_value::<u128>(total_stake);
+    fn require_not_paused(&self) -> Result<()> {
+        if self.halt_state()? == HaltState::Paused {
+            return Err(anyhow!("Pool is paused"));
+        }
+        Ok(())
     }
 
-    fn total_reward_amount_pointer(&self) -> StoragePointer {
-        StoragePointer::from_keyword("/total_reward_amount")
+    fn require_accepting_stake(&self) -> Result<()> {
+        match self.halt_state()? {
+            HaltState::Running => Ok(()),
+            HaltState::Paused => Err(anyhow!("Pool is paused")),
+            HaltState::Cancelled => Err(anyhow!("Pool is cancelled")),
+        }
     }
 
-    fn get_total_reward_amount(&self) -> u128 {
-        self.total_reward_amount_pointer().get_value::<u128>()
+    fn require_no_cooldown(&self) -> Result<()> {
+        if COOLDOWN_BLOCKS.get()? > 0 {
+            return Err(anyhow!("Cooldown is enabled, use RequestUnstake"));
+        }
+        Ok(())
     }
 
-    fn set_total_reward_amount(&self, amount: u128) {
-        self.total_reward_amount_pointer().set_value::<u128>(amount);
+    fn active_position(&self, vault: &AlkaneId) -> Result<Position> {
+        let position = match POSITIONS.try_get(vault)? {
+            Some(position) if position.amount > 0 => position,
+            _ => return Err(anyhow!("Caller is not a staker")),
+        };
+        if position.state != PositionState::Active {
+            return Err(anyhow!("Position is already settled"));
+        }
+        Ok(position)
     }
 
-    // Total staking weight: sum of (user_stake_amount × user_stake_blocks)
-    fn total_stake_weight_pointer(&self) -> StoragePointer {
-        StoragePointer::from_keyword("/total_stake_weight")
+    // Settled rewards, plus the remaining entitlement while still staked.
+    fn total_reward(&self, position: &Position, totals: &PoolTotals) -> u128 {
+        let settled_reward = position.claimed_reward + position.reward_shortfall;
+        match position.state {
+            PositionState::Active => self.calc_reward(position.weight, totals) + settled_reward,
+            _ => settled_reward,
+        }
     }
 
-    fn get_total_stake_weight(&self) -> u128 {
-        self.total_stake_weight_pointer().get_value::<u128>()
+    // If the reward balance cannot cover everything still owed, every claim
+    // is scaled by the same balance / liability ratio, so early claimers are
+    // not paid at the expense of later ones.
+    fn payable_reward(entitlement: u128, balance: u128, liability: u128) -> u128 {
+        if balance >= liability {
+            return entitlement.min(balance);
+        }
+        if balance == 0 {
+            return 0;
+        }
+        match entitlement.checked_mul(balance) {
+            Some(product) => product / liability,
+            // Dividing by the rounded-up ratio can only underpay.
+            None => entitlement / liability.div_ceil(balance),
+        }
     }
 
-    fn set_total_stake_weight(&self, weight: u128) {
-        self.total_stake_weight_pointer().set_value::<u128>(weight);
+    fn outstanding_liability(&self, totals: &PoolTotals) -> Result<u128> {
+        // Nothing is owed once the claim window has closed, or once the
+        // staking period has ended with nobody holding weight.
+        let end_height = END_HEIGHT.get()?;
+        let current_height = self.height();
+        if current_height >= self.claim_deadline()?
+            || (current_height >= end_height && totals.stake_weight == 0)
+        {
+            return Ok(0);
+        }
+        Ok(totals.unsettled_reward())
     }
 
-    fn user_claimed_reward_pointer(&self, alkane_id: &AlkaneId) -> StoragePointer {
-        StoragePointer::from_keyword(
-            format!("/user_claimed_reward/{}:{}", alkane_id.block, alkane_id.tx).as_str(),
-        )
+    fn only_owner(&self) -> Result<()> {
+        let context = self.context()?;
+
+        if context.incoming_alkanes.0.len() != 1 {
+            return Err(anyhow!("did not authenticate with only the collection token"));
+        }
+
+        let transfer = context.incoming_alkanes.0[0].clone();
+        if transfer.id != context.myself.clone() {
+            return Err(anyhow!("supplied alkane is not collection token"));
+        }
+
+        if transfer.value < 1 {
+            return Err(anyhow!("less than 1 unit of collection token supplied to authenticate"));
+        }
+
+        Ok(())
     }
 
-    fn set_user_claimed_reward(&self, alkane_id: &AlkaneId, claimed_reward: u128) {
-        self.user_claimed_reward_pointer(alkane_id).set_value::<u128>(claimed_reward);
+    fn validate_staking_parameters(&self, totals: &PoolTotals, stake_amount: u128) -> Result<()> {
+        if self.height() < START_HEIGHT.get()? {
+            return Err(anyhow!("Staking has not started yet"));
+        }
+
+        // Reject staking when only 1 block away from deadline
+        if self.height() > END_HEIGHT.get()? - 2 {
+            return Err(anyhow!("Staking period has ended"));
+        }
+
+        if totals.stake_amount + stake_amount > MAX_TOTAL_STAKE.get()? {
+            return Err(anyhow!("Total staking amount exceeds maximum limit"));
+        }
+
+        Ok(())
     }
 
-    fn get_user_claimed_reward(&self, alkane_id: &AlkaneId) -> u128 {
-        self.user_claimed_reward_pointer(alkane_id).get_value::<u128>()
+    fn get_staking_token_name(&self) -> Result<String> {
+        let cellpack = Cellpack {
+            target: STAKING_TOKEN_ID.require()?,
+            inputs: vec![99]
+        };
+
+        let call_response =
+            self.staticcall(&cellpack, &AlkaneTransferParcel::default(), self.fuel())?;
+
+        let name_bytes = call_response.data;
+        String::from_utf8(name_bytes).map_err(|e| anyhow!("Failed to parse staking token name: {}", e))
     }
 
     fn get_name(&self) -> Result<CallResponse> {
@@ -591,7 +1618,7 @@ impl StakingPool {
     fn get_total_supply(&self) -> Result<CallResponse> {
         let context = self.context()?;
         let mut response = CallResponse::forward(&context.incoming_alkanes);
-        response.data = self.get_staking_count().to_le_bytes().to_vec();
+        response.data = TOTALS.get()?.staking_count.to_le_bytes().to_vec();
         Ok(response)
     }
 
@@ -603,57 +1630,443 @@ impl StakingPool {
         Ok(response)
     }
 
-    pub fn get_data(&self, _index: u128) -> Result<CallResponse> {
+    fn get_solvency(&self) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+
+        let totals = TOTALS.get()?;
+        let balance = self.balance(&context.myself, &REWARD_TOKEN_ID.require()?);
+        let outstanding_liability = self.outstanding_liability(&totals)?;
+        response.data = Solvency {
+            total_reward_amount: totals.reward_amount,
+            total_claimed: totals.claimed_reward,
+            outstanding_liability,
+            balance,
+            surplus: balance.saturating_sub(outstanding_liability),
+            total_shortfall: totals.reward_shortfall,
+        }
+        .encode();
+        Ok(response)
+    }
+
+    // Response data: the HaltState discriminant as a single byte.
+    fn get_halt_state(&self) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+        response.data = vec![self.halt_state()? as u8];
+        Ok(response)
+    }
+
+    fn get_pool_config(&self) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+        response.data = PoolConfig {
+            reward_token: REWARD_TOKEN_ID.require()?,
+            staking_token: STAKING_TOKEN_ID.require()?,
+            vault_template_id: VAULT_TEMPLATE_ID.get()?,
+            max_total_stake: MAX_TOTAL_STAKE.get()?,
+            start_height: START_HEIGHT.get()?,
+            end_height: END_HEIGHT.get()?,
+            claim_window: self.claim_deadline()? - END_HEIGHT.get()?,
+            cooldown_blocks: COOLDOWN_BLOCKS.get()?,
+            halt_state: self.halt_state()? as u8,
+        }
+        .encode();
+        Ok(response)
+    }
+
+    fn get_pool_totals(&self) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+        response.data = encode_totals(&TOTALS.get()?);
+        Ok(response)
+    }
+
+    // Current phase and the height at which it next changes on its own.
+    fn pool_status(&self) -> Result<(PoolStatus, u64)> {
+        let start_height = START_HEIGHT.get()?;
+        let end_height = END_HEIGHT.get()?;
+        let claim_deadline = self.claim_deadline()?;
+        // validate_staking_parameters accepts stake up to end_height - 2
+        let deposits_close = end_height.saturating_sub(1);
+        let current_height = self.height();
+
+        Ok(match self.halt_state()? {
+            HaltState::Paused => (PoolStatus::Paused, 0),
+            HaltState::Cancelled => (PoolStatus::Cancelled, 0),
+            HaltState::Running if current_height < start_height => {
+                (PoolStatus::NotStarted, start_height)
+            }
+            HaltState::Running if current_height < deposits_close => {
+                (PoolStatus::Open, deposits_close)
+            }
+            HaltState::Running if current_height < end_height => {
+                (PoolStatus::DepositsClosed, end_height)
+            }
+            HaltState::Running if current_height < claim_deadline => {
+                (PoolStatus::ClaimWindow, claim_deadline)
+            }
+            HaltState::Running => (PoolStatus::Expired, 0),
+        })
+    }
+
+    fn get_status(&self) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+
+        let start_height = START_HEIGHT.get()?;
+        let end_height = END_HEIGHT.get()?;
+        let claim_deadline = self.claim_deadline()?;
+        let deposits_close = end_height.saturating_sub(1);
+        let current_height = self.height();
+        let halt_state = self.halt_state()?;
+        let (status, next_transition_height) = self.pool_status()?;
+
+        // Mirrors the checks each operation makes before touching positions
+        let mut allowed_ops = 0;
+        if halt_state != HaltState::Paused {
+            let accepting = halt_state == HaltState::Running;
+            let cooldown = COOLDOWN_BLOCKS.get()? > 0;
+            if accepting && current_height >= start_height && current_height < deposits_close {
+                allowed_ops |= views::OP_STAKE | views::OP_INCREASE_STAKE;
+            }
+            if accepting && current_height < end_height {
+                allowed_ops |= views::OP_SYNC;
+            }
+            allowed_ops |= if cooldown {
+                views::OP_REQUEST_UNSTAKE | views::OP_COMPLETE_UNSTAKE
+            } else {
+                views::OP_UNSTAKE
+                    | views::OP_PARTIAL_UNSTAKE
+                    | views::OP_MIGRATE
+                    | views::OP_SET_AUTO_RENEW
+            };
+            allowed_ops |= views::OP_MERGE | views::OP_SPLIT | views::OP_BATCH_UNSTAKE;
+            if current_height >= end_height && RENEW_TARGET.try_get()?.is_some() {
+                allowed_ops |= views::OP_ROLL;
+            }
+            if current_height >= claim_deadline {
+                allowed_ops |= views::OP_WITHDRAW;
+            }
+        }
+
+        response.data = Status {
+            status,
+            next_transition_height,
+            allowed_ops,
+        }
+        .encode();
+        Ok(response)
+    }
+
+    // Runs Stake's checks and weight math for `amount` at the current height
+    // without recording anything.
+    fn preview_stake(&self, amount: u128) -> Result<CallResponse> {
         let context = self.context()?;
         let mut response = CallResponse::forward(&context.incoming_alkanes);
-        response.data = COLLECTION_IMAGE.to_vec();
+
+        let mut totals = TOTALS.get()?;
+        self.require_accepting_stake()?;
+        self.validate_staking_parameters(&totals, amount)?;
+
+        let stake_blocks = END_HEIGHT.get()? as u128 - self.height() as u128;
+        let weight = stake_blocks.saturating_mul(amount);
+        totals.stake_weight = totals.stake_weight.saturating_add(weight);
+
+        response.data = StakePreview {
+            weight,
+            total_weight: totals.stake_weight,
+            share: mul_div(weight, RATE_PRECISION, totals.stake_weight.max(1)),
+            reward: self.calc_reward(weight, &totals),
+        }
+        .encode();
         Ok(response)
     }
 
+    // Response data: number of syncs (u128 LE), followed by the amount
+    // (u128 LE) and height (u128 LE) of sync `index` when it exists.
+    fn get_sync_record(&self, index: u128) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+
+        let mut data = Writer::new();
+        data.put_u128(SYNC_LOG.len()?);
+        if let Some(record) = SYNC_LOG.get(index)? {
+            data.put_u128(record.amount).put_u128(record.height as u128);
+        }
+        response.data = data.into_inner();
+        Ok(response)
+    }
+
+    fn get_reward_rate(&self) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+
+        // One staked unit for one block carries a weight of one. calc_reward
+        // would overflow on RATE_PRECISION × reward_amount for large budgets.
+        let totals = TOTALS.get()?;
+        let rate = if totals.stake_weight == 0 {
+            0
+        } else {
+            mul_div(totals.reward_amount, RATE_PRECISION, totals.stake_weight)
+        };
+        response.data = RewardRate {
+            rate,
+            remaining_budget: totals.unsettled_reward(),
+            total_weight: totals.stake_weight,
+        }
+        .encode();
+        Ok(response)
+    }
+
+    fn get_position(&self, index: u128) -> Result<CallResponse> {
+        let vault_id = VAULT_BY_INDEX
+            .try_get(&index)?
+            .ok_or_else(|| anyhow!("No position with index {}", index))?;
+        self.get_position_by_vault(vault_id)
+    }
+
+    fn get_position_by_vault(&self, vault_id: AlkaneId) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+
+        let position = POSITIONS
+            .try_get(&vault_id)?
+            .ok_or_else(|| anyhow!("Vault is not a position of this pool"))?;
+        let totals = TOTALS.get()?;
+        response.data = PositionView {
+            vault_id,
+            position: &position,
+            total_reward: self.total_reward(&position, &totals),
+        }
+        .encode();
+        Ok(response)
+    }
+
+    // Walks up to `limit` staking indices from `offset` (0 is index 1) and
+    // returns the positions that pass the filters. `filter` is a bit mask of
+    // PositionState discriminants (bit 0 active, bit 1 exited, bit 2 claimed,
+    // bit 3 merged, bit 4 unbonding); 0 accepts every state. Positions with
+    // less than `min_amount` staked are skipped.
+    //
+    // Response data: next offset (u128 LE) | staking count (u128 LE), then one
+    // 145-byte entry per match: index (u128 LE) | position record as in
+    // GetPosition. Paging is done once the next offset reaches the count.
+    fn list_positions(
+        &self,
+        offset: u128,
+        limit: u128,
+        filter: u128,
+        min_amount: u128,
+    ) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+
+        let totals = TOTALS.get()?;
+        let start = offset.min(totals.staking_count);
+        let end = start.saturating_add(limit.min(MAX_LIST_LIMIT)).min(totals.staking_count);
+
+        let mut data = Writer::new();
+        data.put_u128(end).put_u128(totals.staking_count);
+        for index in start + 1..=end {
+            let Some(vault_id) = VAULT_BY_INDEX.try_get(&index)? else {
+                continue;
+            };
+            let position = POSITIONS.require(&vault_id)?;
+            if filter != 0 && filter & (1 << position.state as u8) == 0 {
+                continue;
+            }
+            if position.amount < min_amount {
+                continue;
+            }
+            let record = PositionView {
+                vault_id,
+                position: &position,
+                total_reward: self.total_reward(&position, &totals),
+            };
+            data.put_u128(index).put_bytes(&record.encode());
+        }
+        response.data = data.into_inner();
+        Ok(response)
+    }
+
+    // A vault index gets that vault's live SVG card; anything else, such as
+    // index 0, the collection image.
+    pub fn get_data(&self, index: u128) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+
+        let Some(vault_id) = VAULT_BY_INDEX.try_get(&index)? else {
+            response.data = self.collection_image()?;
+            return Ok(response);
+        };
+        let position = POSITIONS.require(&vault_id)?;
+        let card = VaultCard {
+            index,
+            pool_name: &self.name(),
+            position: &position,
+            total_reward: self.total_reward(&position, &TOTALS.get()?),
+            phase: self.pool_status()?.0,
+        };
+        response.data = art::vault_svg(&card).into_bytes();
+        Ok(response)
+    }
+
+    fn get_content_type(&self, index: u128) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+        let content_type = if VAULT_BY_INDEX.try_get(&index)?.is_some() {
+            "image/svg+xml".to_string()
+        } else {
+            IMAGE_LIVE_CONTENT_TYPE
+                .try_get()?
+                .unwrap_or_else(|| "image/png".to_string())
+        };
+        response.data = content_type.into_bytes();
+        Ok(response)
+    }
+
+    // The finalized upload if there is one, else the built-in PNG.
+    fn collection_image(&self) -> Result<Vec<u8>> {
+        let chunks = IMAGE_LIVE_CHUNKS.get()?;
+        if chunks == 0 {
+            return Ok(COLLECTION_IMAGE.to_vec());
+        }
+        let slot = IMAGE_LIVE_SLOT.get()?;
+        let mut image = Vec::new();
+        for index in 0..chunks {
+            image.extend(IMAGE_CHUNKS.require(&image_chunk_key(slot, index))?);
+        }
+        Ok(image)
+    }
+
+    // Any caller other than a vault of this pool gets the collection's NFT
+    // metadata document. Vaults of this pool still get their GetPositionInfo
+    // document, as they did before the metadata format existed.
     pub fn get_attributes(&self) -> Result<CallResponse> {
         let context = self.context()?;
         let mut response = CallResponse::forward(&context.incoming_alkanes);
+        response.data = match POSITIONS.try_get(&context.caller)? {
+            Some(position) => self.position_info_json(&context.caller, &position)?,
+            None => self.collection_metadata()?.to_json(),
+        }
+        .into_bytes();
+        Ok(response)
+    }
+
+    fn get_pool_info(&self) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+        response.data = self.pool_info_json()?.into_bytes();
+        Ok(response)
+    }
 
-        let end_height = self.get_end_height() as u128;
-
-        // Query caller's staking information
-        let stake_block = self.get_stake_block(&context.caller);
-        let stake_amount = self.get_stake_amount(&context.caller);
-
-        // If no staking info, return staking pool information
-        if stake_block == 0 || stake_amount == 0 {
-            let stake_alkane = self.get_staking_token_id();
-            let reward_alkane = self.get_reward_token_id();
-            let pool_info = format!(
-                r#"{{"start_block":{},"end_block":{},"staking_token":"{}","reward_token":"{}","max_total_stake":"{}","total_stake_amount":"{}","total_reward_amount":"{}","claimable_reward_amount":"{}"}}"#,
-                self.get_start_height(),
-                end_height,
-                format!("{}:{}", stake_alkane.block, stake_alkane.tx).as_str(),
-                format!("{}:{}", reward_alkane.block, reward_alkane.tx).as_str(),
-                self.get_max_total_stake(),
-                self.get_total_stake_amount(),
-                self.get_total_reward_amount(),
-                self.balance(&context.myself, &reward_alkane)
+    fn get_position_info(&self, vault_id: AlkaneId) -> Result<CallResponse> {
+        let context = self.context()?;
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+        let position = POSITIONS
+            .try_get(&vault_id)?
+            .ok_or_else(|| anyhow!("Vault is not a position of this pool"))?;
+        response.data = self.position_info_json(&vault_id, &position)?.into_bytes();
+        Ok(response)
+    }
+
+    fn collection_metadata(&self) -> Result<NftMetadata> {
+        let context = self.context()?;
+        let totals = TOTALS.get()?;
+        let stake_alkane = STAKING_TOKEN_ID.require()?;
+        let reward_alkane = REWARD_TOKEN_ID.require()?;
+        let (status, _) = self.pool_status()?;
+        Ok(NftMetadata {
+            name: self.name(),
+            description: format!(
+                "Staking pool for {}:{} paying rewards in {}:{}. Each position is a vault NFT.",
+                stake_alkane.block, stake_alkane.tx, reward_alkane.block, reward_alkane.tx
+            ),
+            image: format!("alkane:{}:{}", context.myself.block, context.myself.tx),
+            attributes: vec![
+                Attribute::text("status", status.as_str()),
+                Attribute::number("start_block", START_HEIGHT.get()?),
+                Attribute::number("end_block", END_HEIGHT.get()?),
+                Attribute::text("staking_token", format!("{}:{}", stake_alkane.block, stake_alkane.tx)),
+                Attribute::text("reward_token", format!("{}:{}", reward_alkane.block, reward_alkane.tx)),
+                Attribute::amount("max_total_stake", MAX_TOTAL_STAKE.get()?),
+                Attribute::amount("staking_count", totals.staking_count),
+                Attribute::amount("total_stake_amount", totals.stake_amount),
+                Attribute::amount("total_reward_amount", totals.reward_amount),
+                Attribute::amount("claimed_reward_amount", totals.claimed_reward),
+                Attribute::amount(
+                    "claimable_reward_amount",
+                    self.balance(&context.myself, &reward_alkane),
+                ),
+            ],
+        })
+    }
+
+    // Heights are JSON numbers; token amounts are decimal strings because
+    // they can exceed what JSON numbers represent exactly.
+    fn pool_info_json(&self) -> Result<String> {
+        let context = self.context()?;
+        let totals = TOTALS.get()?;
+        let stake_alkane = STAKING_TOKEN_ID.require()?;
+        let reward_alkane = REWARD_TOKEN_ID.require()?;
+        let mut info = JsonObject::new();
+        info.number("start_block", START_HEIGHT.get()?)
+            .number("end_block", END_HEIGHT.get()?)
+            .string("staking_token", &format!("{}:{}", stake_alkane.block, stake_alkane.tx))
+            .string("reward_token", &format!("{}:{}", reward_alkane.block, reward_alkane.tx))
+            .string("max_total_stake", &MAX_TOTAL_STAKE.get()?.to_string())
+            .string("staking_count", &totals.staking_count.to_string())
+            .string("total_stake_amount", &totals.stake_amount.to_string())
+            .string("total_stake_weight", &totals.stake_weight.to_string())
+            .string("total_reward_amount", &totals.reward_amount.to_string())
+            .string("claimed_reward_amount", &totals.claimed_reward.to_string())
+            .string(
+                "claimable_reward_amount",
+                &self.balance(&context.myself, &reward_alkane).to_string(),
             );
-            response.data = pool_info.into_bytes();
-            return Ok(response)
-        }
-        
+        Ok(info.finish())
+    }
+
+    fn position_info_json(&self, vault_id: &AlkaneId, position: &Position) -> Result<String> {
         // Calculate total reward that can be mined (user's full entitlement)
-        let total_reward = self.calc_reward(&context.caller);
+        let total_reward = self.total_reward(position, &TOTALS.get()?);
+        let mut info = JsonObject::new();
+        info.string("vault_id", &format!("{}:{}", vault_id.block, vault_id.tx))
+            .number("stake_block", position.stake_height)
+            .string("stake_amount", &position.amount.to_string())
+            .string("stake_blocks", &position.stake_blocks.to_string())
+            .string("weight", &position.weight.to_string())
+            .string("total_reward", &total_reward.to_string())
+            .string("claimed_reward", &position.claimed_reward.to_string())
+            .string("state", position.state.as_str())
+            .number("unlock_height", position.unlock_height)
+            .bool("auto_renew", position.auto_renew);
+        Ok(info.finish())
+    }
+}
 
-        // Get whether user has claimed rewards (omit mined progress since current height
-        // is unavailable/restricted in this execution context)
-        let claimed_reward = self.get_user_claimed_reward(&context.caller);
-        let stake_blocks = self.get_stake_blocks(&context.caller);
-        
-        let stake_info = format!(
-            r#"{{"stake_block":{},"stake_amount":"{}","stake_blocks":"{}","total_reward":"{}","claimed_reward":"{}"}}"#,
-            stake_block, stake_amount, stake_blocks, total_reward, claimed_reward
-        );
-        response.data = stake_info.into_bytes();
-        Ok(response)
+// a × b / c, falling back to (a / c) × b when the product overflows.
+fn mul_div(a: u128, b: u128, c: u128) -> u128 {
+    match a.checked_mul(b) {
+        Some(product) => product / c,
+        None => (a / c).saturating_mul(b),
+    }
+}
+
+fn image_chunk_key(slot: u128, index: u128) -> u128 {
+    (slot << 64) | index
+}
+
+// Reads `length` bytes packed 16 to a little-endian u128 from call inputs.
+fn unpack_input_bytes(words: &[u128], length: u128) -> Result<Vec<u8>> {
+    let length = usize::try_from(length).map_err(|_| anyhow!("Byte length is too large"))?;
+    if words.len() != length.div_ceil(16) {
+        return Err(anyhow!("Expected {} input words for {} bytes", length.div_ceil(16), length));
     }
+    let mut bytes: Vec<u8> = words.iter().flat_map(|word| word.to_le_bytes()).collect();
+    bytes.truncate(length);
+    Ok(bytes)
 }
 
 declare_alkane! {
@@ -0,0 +1,209 @@
+use alkanes_common::{Reader, Record, Writer};
+use anyhow::{Result, anyhow};
+
+/// Lifecycle of a position. Settled positions keep their data because the
+/// reward distribution references historical weights.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum PositionState {
+    #[default]
+    Active = 0,
+    /// Withdrawn before the staking period ended, without rewards.
+    Exited = 1,
+    /// Withdrawn after the staking period ended, rewards settled.
+    Claimed = 2,
+    /// Folded into another vault of the same pool by `Merge`.
+    Merged = 3,
+    /// Unstake requested in cooldown mode: rewards are settled and the
+    /// principal stays in the vault until `unlock_height`.
+    Unbonding = 4,
+}
+
+impl PositionState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Active => "active",
+            Self::Exited => "exited",
+            Self::Claimed => "claimed",
+            Self::Merged => "merged",
+            Self::Unbonding => "unbonding",
+        }
+    }
+}
+
+impl TryFrom<u8> for PositionState {
+    type Error = anyhow::Error;
+
+    fn try_from(value: u8) -> Result<Self> {
+        match value {
+            0 => Ok(Self::Active),
+            1 => Ok(Self::Exited),
+            2 => Ok(Self::Claimed),
+            3 => Ok(Self::Merged),
+            4 => Ok(Self::Unbonding),
+            _ => Err(anyhow!("invalid position state {}", value)),
+        }
+    }
+}
+
+/// Whether the owner has stopped the pool. Vaults read this through
+/// `GetHaltState` to decide if their emergency exit is open.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum HaltState {
+    #[default]
+    Running = 0,
+    /// Temporarily stopped by the owner; every user operation is rejected
+    /// until `Unpause`.
+    Paused = 1,
+    /// Permanently stopped: no new stake is accepted, exits keep working.
+    Cancelled = 2,
+}
+
+impl TryFrom<u8> for HaltState {
+    type Error = anyhow::Error;
+
+    fn try_from(value: u8) -> Result<Self> {
+        match value {
+            0 => Ok(Self::Running),
+            1 => Ok(Self::Paused),
+            2 => Ok(Self::Cancelled),
+            _ => Err(anyhow!("invalid halt state {}", value)),
+        }
+    }
+}
+
+/// Everything the pool knows about one vault, stored as a single record
+/// keyed by the vault's `AlkaneId`.
+#[derive(Clone, Default)]
+pub struct Position {
+    /// Height at which the position was opened.
+    pub stake_height: u64,
+    pub amount: u128,
+    /// Blocks from `stake_height` to the end of the staking period. After a
+    /// top-up or merge this is the amount-weighted average, `weight / amount`.
+    pub stake_blocks: u128,
+    /// `amount × stake_blocks` summed over every deposit into the position,
+    /// kept so reward math needs no recomputation.
+    pub weight: u128,
+    pub claimed_reward: u128,
+    /// Part of the entitlement left unpaid because the pool was short of
+    /// reward tokens when the position was settled.
+    pub reward_shortfall: u128,
+    pub state: PositionState,
+    /// Height at which an unstake was requested in cooldown mode; zero
+    /// otherwise.
+    pub request_height: u64,
+    /// Height from which an unbonding position can complete its unstake;
+    /// zero unless an unstake was requested in cooldown mode.
+    pub unlock_height: u64,
+    /// Set by the holder to have `Roll` carry the principal into the renew
+    /// target once the staking period ends.
+    pub auto_renew: bool,
+}
+
+impl Record for Position {
+    const VERSION: u8 = 6;
+
+    fn encode_body(&self, writer: &mut Writer) {
+        writer
+            .put_u64(self.stake_height)
+            .put_u128(self.amount)
+            .put_u128(self.stake_blocks)
+            .put_u128(self.weight)
+            .put_u128(self.claimed_reward)
+            .put_u128(self.reward_shortfall)
+            .put_u8(self.state as u8)
+            .put_u64(self.request_height)
+            .put_u64(self.unlock_height)
+            .put_bool(self.auto_renew);
+    }
+
+    fn decode_body(reader: &mut Reader) -> Result<Self> {
+        Ok(Self {
+            stake_height: reader.read_u64()?,
+            amount: reader.read_u128()?,
+            stake_blocks: reader.read_u128()?,
+            weight: reader.read_u128()?,
+            claimed_reward: reader.read_u128()?,
+            reward_shortfall: reader.read_u128()?,
+            state: reader.read_u8()?.try_into()?,
+            request_height: reader.read_u64()?,
+            unlock_height: reader.read_u64()?,
+            auto_renew: reader.read_bool()?,
+        })
+    }
+}
+
+/// Pool-wide counters that change together on every stake and unstake, so
+/// they are read and written as one record.
+#[derive(Clone, Default)]
+pub struct PoolTotals {
+    pub staking_count: u128,
+    /// Sum of all positions' `stake_blocks`.
+    pub stake_blocks: u128,
+    pub stake_amount: u128,
+    /// Sum of all positions' `weight`.
+    pub stake_weight: u128,
+    pub reward_amount: u128,
+    /// Rewards paid out to positions so far.
+    pub claimed_reward: u128,
+    /// Sum of all positions' `reward_shortfall`.
+    pub reward_shortfall: u128,
+}
+
+impl PoolTotals {
+    /// Rewards still owed to positions that have not settled yet.
+    pub fn unsettled_reward(&self) -> u128 {
+        self.reward_amount
+            .saturating_sub(self.claimed_reward)
+            .saturating_sub(self.reward_shortfall)
+    }
+}
+
+impl Record for PoolTotals {
+    const VERSION: u8 = 2;
+
+    fn encode_body(&self, writer: &mut Writer) {
+        writer
+            .put_u128(self.staking_count)
+            .put_u128(self.stake_blocks)
+            .put_u128(self.stake_amount)
+            .put_u128(self.stake_weight)
+            .put_u128(self.reward_amount)
+            .put_u128(self.claimed_reward)
+            .put_u128(self.reward_shortfall);
+    }
+
+    fn decode_body(reader: &mut Reader) -> Result<Self> {
+        Ok(Self {
+            staking_count: reader.read_u128()?,
+            stake_blocks: reader.read_u128()?,
+            stake_amount: reader.read_u128()?,
+            stake_weight: reader.read_u128()?,
+            reward_amount: reader.read_u128()?,
+            claimed_reward: reader.read_u128()?,
+            reward_shortfall: reader.read_u128()?,
+        })
+    }
+}
+
+/// One `Sync` that folded surplus reward tokens into the distribution.
+#[derive(Clone, Default)]
+pub struct SyncRecord {
+    pub amount: u128,
+    pub height: u64,
+}
+
+impl Record for SyncRecord {
+    const VERSION: u8 = 1;
+
+    fn encode_body(&self, writer: &mut Writer) {
+        writer.put_u128(self.amount).put_u64(self.height);
+    }
+
+    fn decode_body(reader: &mut Reader) -> Result<Self> {
+        Ok(Self {
+            amount: reader.read_u128()?,
+            height: reader.read_u64()?,
+        })
+    }
+}
@@ -0,0 +1,44 @@
+use super::*;
+
+#[test]
+fn solvent_pool_pays_full_entitlement() {
+    assert_eq!(StakingPool::payable_reward(400, 1_000, 1_000), 400);
+    assert_eq!(StakingPool::payable_reward(400, 5_000, 1_000), 400);
+    // Never more than the pool holds
+    assert_eq!(StakingPool::payable_reward(400, 300, 0), 300);
+}
+
+#[test]
+fn short_pool_pays_every_claim_the_same_ratio() {
+    // 500 tokens against 1000 owed: every claim gets half
+    let mut balance = 500u128;
+    let mut liability = 1_000u128;
+    for entitlement in [400u128, 100, 500] {
+        let paid = StakingPool::payable_reward(entitlement, balance, liability);
+        assert_eq!(paid * 2, entitlement);
+        balance -= paid;
+        // A settled entitlement leaves the liability whether paid or cut
+        liability -= entitlement;
+    }
+    assert_eq!(balance, 0);
+    assert_eq!(liability, 0);
+}
+
+#[test]
+fn empty_pool_pays_nothing() {
+    assert_eq!(StakingPool::payable_reward(400, 0, 1_000), 0);
+}
+
+#[test]
+fn overflow_fallback_only_underpays() {
+    // entitlement × balance overflows in both cases; exact results worked
+    // out by hand.
+    // u128::MAX × 7 / u128::MAX = 7
+    let paid = StakingPool::payable_reward(u128::MAX, 7, u128::MAX);
+    assert!(paid <= 7);
+    assert_eq!(paid, 6);
+
+    // (2^127 - 1) × 2^64 / (2^128 - 1) rounds down to 2^63 - 1
+    let paid = StakingPool::payable_reward(u128::MAX / 2, 1 << 64, u128::MAX);
+    assert!(paid <= (1 << 63) - 1);
+}
@@ -0,0 +1,304 @@
+use alkanes_common::Writer;
+use alkanes_support::id::AlkaneId;
+
+use crate::state::{PoolTotals, Position};
+
+/// Reward-token solvency of the pool, returned by `GetSolvency`.
+///
+/// Encoded as six little-endian `u128` words (96 bytes):
+///
+/// | offset | field                 |
+/// |--------|-----------------------|
+/// | 0      | total_reward_amount   |
+/// | 16     | total_claimed         |
+/// | 32     | outstanding_liability |
+/// | 48     | balance               |
+/// | 64     | surplus               |
+/// | 80     | total_shortfall       |
+pub struct Solvency {
+    pub total_reward_amount: u128,
+    pub total_claimed: u128,
+    /// Rewards the pool can still be asked to pay; zero once the claim
+    /// window has closed.
+    pub outstanding_liability: u128,
+    pub balance: u128,
+    /// `balance - outstanding_liability`, or zero when the pool is short.
+    pub surplus: u128,
+    /// Entitlements cut by pro-rata payouts so far.
+    pub total_shortfall: u128,
+}
+
+impl Solvency {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut writer = Writer::new();
+        writer
+            .put_u128(self.total_reward_amount)
+            .put_u128(self.total_claimed)
+            .put_u128(self.outstanding_liability)
+            .put_u128(self.balance)
+            .put_u128(self.surplus)
+            .put_u128(self.total_shortfall);
+        writer.into_inner()
+    }
+}
+
+/// One position as returned by `GetPosition` and `GetPositionByVault`.
+///
+/// Encoded as 146 bytes, all integers little-endian:
+///
+/// | offset | field          | size |
+/// |--------|----------------|------|
+/// | 0      | vault_id.block | 16   |
+/// | 16     | vault_id.tx    | 16   |
+/// | 32     | amount         | 16   |
+/// | 48     | stake_height   | 16   |
+/// | 64     | stake_blocks   | 16   |
+/// | 80     | weight         | 16   |
+/// | 96     | total_reward   | 16   |
+/// | 112    | claimed_reward | 16   |
+/// | 128    | state          | 1    |
+/// | 129    | unlock_height  | 16   |
+/// | 145    | auto_renew     | 1    |
+///
+/// `state` is the `PositionState` discriminant: 0 active, 1 exited,
+/// 2 claimed, 3 merged, 4 unbonding. `unlock_height` is zero unless an
+/// unstake was requested in cooldown mode.
+pub struct PositionView<'a> {
+    pub vault_id: AlkaneId,
+    pub position: &'a Position,
+    /// Settled rewards plus, while the position is active, its current
+    /// entitlement.
+    pub total_reward: u128,
+}
+
+impl PositionView<'_> {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut writer = Writer::new();
+        writer
+            .put_alkane_id(&self.vault_id)
+            .put_u128(self.position.amount)
+            .put_u128(self.position.stake_height as u128)
+            .put_u128(self.position.stake_blocks)
+            .put_u128(self.position.weight)
+            .put_u128(self.total_reward)
+            .put_u128(self.position.claimed_reward)
+            .put_u8(self.position.state as u8)
+            .put_u128(self.position.unlock_height as u128)
+            .put_bool(self.position.auto_renew);
+        writer.into_inner()
+    }
+}
+
+/// Fixed pool parameters, returned by `GetPoolConfig`.
+///
+/// Encoded as 176 bytes, all integers little-endian `u128`:
+///
+/// | offset | field              |
+/// |--------|--------------------|
+/// | 0      | reward_token.block |
+/// | 16     | reward_token.tx    |
+/// | 32     | staking_token.block|
+/// | 48     | staking_token.tx   |
+/// | 64     | vault_template_id  |
+/// | 80     | max_total_stake    |
+/// | 96     | start_height       |
+/// | 112    | end_height         |
+/// | 128    | claim_window       |
+/// | 144    | cooldown_blocks    |
+/// | 160    | halt_state         |
+pub struct PoolConfig {
+    pub reward_token: AlkaneId,
+    pub staking_token: AlkaneId,
+    pub vault_template_id: u128,
+    pub max_total_stake: u128,
+    pub start_height: u64,
+    pub end_height: u64,
+    /// Blocks after `end_height` that rewards can be claimed, including the
+    /// extension from time spent paused.
+    pub claim_window: u64,
+    pub cooldown_blocks: u64,
+    /// `HaltState` discriminant: 0 running, 1 paused, 2 cancelled.
+    pub halt_state: u8,
+}
+
+impl PoolConfig {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut writer = Writer::new();
+        writer
+            .put_alkane_id(&self.reward_token)
+            .put_alkane_id(&self.staking_token)
+            .put_u128(self.vault_template_id)
+            .put_u128(self.max_total_stake)
+            .put_u128(self.start_height as u128)
+            .put_u128(self.end_height as u128)
+            .put_u128(self.claim_window as u128)
+            .put_u128(self.cooldown_blocks as u128)
+            .put_u128(self.halt_state as u128);
+        writer.into_inner()
+    }
+}
+
+/// Pool-wide counters, returned by `GetPoolTotals`.
+///
+/// Encoded as seven little-endian `u128` words (112 bytes):
+///
+/// | offset | field           |
+/// |--------|-----------------|
+/// | 0      | staking_count   |
+/// | 16     | stake_blocks    |
+/// | 32     | stake_amount    |
+/// | 48     | stake_weight    |
+/// | 64     | reward_amount   |
+/// | 80     | claimed_reward  |
+/// | 96     | reward_shortfall|
+pub fn encode_totals(totals: &PoolTotals) -> Vec<u8> {
+    let mut writer = Writer::new();
+    writer
+        .put_u128(totals.staking_count)
+        .put_u128(totals.stake_blocks)
+        .put_u128(totals.stake_amount)
+        .put_u128(totals.stake_weight)
+        .put_u128(totals.reward_amount)
+        .put_u128(totals.claimed_reward)
+        .put_u128(totals.reward_shortfall);
+    writer.into_inner()
+}
+
+/// Phase of the pool as reported by `GetStatus`. Halt states take precedence
+/// over the height-based phases.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PoolStatus {
+    NotStarted = 0,
+    /// Stake and IncreaseStake are accepted.
+    Open = 1,
+    /// The last block before `end_height`; `validate_staking_parameters`
+    /// already rejects new stake.
+    DepositsClosed = 2,
+    ClaimWindow = 3,
+    Expired = 4,
+    Cancelled = 5,
+    Paused = 6,
+}
+
+impl PoolStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::NotStarted => "Not started",
+            Self::Open => "Open",
+            Self::DepositsClosed => "Deposits closed",
+            Self::ClaimWindow => "Claim window",
+            Self::Expired => "Expired",
+            Self::Cancelled => "Cancelled",
+            Self::Paused => "Paused",
+        }
+    }
+}
+
+// Bits of `Status::allowed_ops`, one per pool operation.
+pub const OP_STAKE: u128 = 1 << 0;
+pub const OP_INCREASE_STAKE: u128 = 1 << 1;
+pub const OP_UNSTAKE: u128 = 1 << 2;
+pub const OP_PARTIAL_UNSTAKE: u128 = 1 << 3;
+pub const OP_MERGE: u128 = 1 << 4;
+pub const OP_SPLIT: u128 = 1 << 5;
+pub const OP_BATCH_UNSTAKE: u128 = 1 << 6;
+pub const OP_REQUEST_UNSTAKE: u128 = 1 << 7;
+pub const OP_COMPLETE_UNSTAKE: u128 = 1 << 8;
+pub const OP_MIGRATE: u128 = 1 << 9;
+pub const OP_SET_AUTO_RENEW: u128 = 1 << 10;
+pub const OP_ROLL: u128 = 1 << 11;
+pub const OP_SYNC: u128 = 1 << 12;
+pub const OP_WITHDRAW: u128 = 1 << 13;
+
+/// Returned by `GetStatus`.
+///
+/// Encoded as three little-endian `u128` words (48 bytes):
+///
+/// | offset | field                  |
+/// |--------|------------------------|
+/// | 0      | status                 |
+/// | 16     | next_transition_height |
+/// | 32     | allowed_ops            |
+pub struct Status {
+    pub status: PoolStatus,
+    /// First height at which the status changes on its own; zero when only
+    /// an owner action can change it.
+    pub next_transition_height: u64,
+    /// `OP_*` bits of the operations the pool would accept now.
+    pub allowed_ops: u128,
+}
+
+impl Status {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut writer = Writer::new();
+        writer
+            .put_u128(self.status as u128)
+            .put_u128(self.next_transition_height as u128)
+            .put_u128(self.allowed_ops);
+        writer.into_inner()
+    }
+}
+
+/// Fixed-point scale used for ratios in `StakePreview` and `RewardRate`.
+pub const RATE_PRECISION: u128 = 1_000_000_000_000_000_000;
+
+/// Returned by `PreviewStake`.
+///
+/// Encoded as four little-endian `u128` words (64 bytes):
+///
+/// | offset | field        |
+/// |--------|--------------|
+/// | 0      | weight       |
+/// | 16     | total_weight |
+/// | 32     | share        |
+/// | 48     | reward       |
+pub struct StakePreview {
+    pub weight: u128,
+    /// Pool weight after the simulated stake.
+    pub total_weight: u128,
+    /// `weight / total_weight`, scaled by `RATE_PRECISION`.
+    pub share: u128,
+    /// Reward the new position would be entitled to if nothing else changed.
+    pub reward: u128,
+}
+
+impl StakePreview {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut writer = Writer::new();
+        writer
+            .put_u128(self.weight)
+            .put_u128(self.total_weight)
+            .put_u128(self.share)
+            .put_u128(self.reward);
+        writer.into_inner()
+    }
+}
+
+/// Returned by `GetRewardRate`.
+///
+/// Encoded as three little-endian `u128` words (48 bytes):
+///
+/// | offset | field            |
+/// |--------|------------------|
+/// | 0      | rate             |
+/// | 16     | remaining_budget |
+/// | 32     | total_weight     |
+pub struct RewardRate {
+    /// Reward per staked unit per block, scaled by `RATE_PRECISION`; zero
+    /// while the pool holds no weight.
+    pub rate: u128,
+    /// Rewards not yet paid out or cut by a shortfall.
+    pub remaining_budget: u128,
+    pub total_weight: u128,
+}
+
+impl RewardRate {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut writer = Writer::new();
+        writer
+            .put_u128(self.rate)
+            .put_u128(self.remaining_budget)
+            .put_u128(self.total_weight);
+        writer.into_inner()
+    }
+}
@@ -1,11 +1,12 @@
 use metashrew_support::compat::to_arraybuffer_layout;
-use metashrew_support::index_pointer::KeyValuePointer;
 
 use alkanes_runtime::{
-    declare_alkane, message::MessageDispatch, runtime::AlkaneResponder, storage::StoragePointer,
-    token::Token,
+    declare_alkane, message::MessageDispatch, runtime::AlkaneResponder, token::Token,
 };
 
+use alkanes_common::{
+    Attribute, Cell, List, Map, NftMetadata, Reader, ReentrancyLock, StringCell, Writer,
+};
 use alkanes_support::{
     cellpack::Cellpack,
     id::AlkaneId,
@@ -14,10 +15,40 @@ use alkanes_support::{
 };
 
 use anyhow::{Result, anyhow};
-use std::sync::Arc;
 
-const COLLECTION_NAME: &str = "LP Staking";
-const COLLECTION_SYMBOL: &str = "LPS";
+// Used when the pool's name or symbol could not be read at initialize.
+const FALLBACK_NAME: &str = "LP Staking";
+const FALLBACK_SYMBOL: &str = "LPS";
+const MAX_NAME_LEN: usize = 256;
+const MAX_SYMBOL_LEN: usize = 64;
+
+const COLLECTION_ALKANE_ID: Cell<AlkaneId> = Cell::new("/collection-alkane-id");
+const INDEX: Cell<u128> = Cell::new("/index");
+const STAKING_TOKEN_ID: Cell<AlkaneId> = Cell::new("/staking-token-id");
+// The pool's name and symbol, copied at initialize.
+const POOL_NAME: StringCell = StringCell::new("/pool-name", MAX_NAME_LEN);
+const POOL_SYMBOL: StringCell = StringCell::new("/pool-symbol", MAX_SYMBOL_LEN);
+// Height recorded by the pool at creation after which EmergencyUnstake is
+// open regardless of the pool's state.
+const SAFETY_HEIGHT: Cell<u64> = Cell::new("/safety-height");
+
+// Every alkane id the vault has been sent, in order of first arrival, so
+// Sweep can find stray tokens; balances cannot be enumerated on-chain.
+const INVENTORY: List<AlkaneId> = List::new("/inventory");
+const IN_INVENTORY: Map<AlkaneId, bool> = Map::new("/inventory-known/");
+
+// Pool token opcodes for the collection name and symbol.
+const POOL_GET_NAME: u128 = 99;
+const POOL_GET_SYMBOL: u128 = 100;
+
+// Pool opcode reporting its halt state as one byte: 1 paused, 2 cancelled.
+const POOL_GET_HALT_STATE: u128 = 201;
+// Pool opcode returning a vault's position as a fixed binary record.
+const POOL_GET_POSITION_BY_VAULT: u128 = 203;
+
+// Held while the vault calls into its pool, so the pool (or anything it
+// calls) cannot re-enter the vault mid-operation.
+const REENTRANCY_LOCK: ReentrancyLock = ReentrancyLock::new("/lock");
 
 #[derive(Default)]
 pub struct StakingVault(());
@@ -27,11 +58,46 @@ impl AlkaneResponder for StakingVault {}
 #[derive(MessageDispatch)]
 enum StakingVaultMessage {
     #[opcode(0)]
-    Initialize { index: u128 },
+    Initialize {
+        index: u128,
+        staking_token_id: AlkaneId,
+        safety_height: u128,
+    },
+
+    #[opcode(40)]
+    Deposit,
+
+    #[opcode(41)]
+    Release { alkane_id: AlkaneId, amount: u128 },
 
     #[opcode(51)]
     Unstake,
 
+    #[opcode(52)]
+    PartialUnstake { amount: u128 },
+
+    #[opcode(55)]
+    Split { amount: u128 },
+
+    #[opcode(57)]
+    RequestUnstake,
+
+    #[opcode(58)]
+    CompleteUnstake,
+
+    #[opcode(59)]
+    Migrate { successor: AlkaneId },
+
+    #[opcode(60)]
+    SetAutoRenew { enabled: u128 },
+
+    #[opcode(90)]
+    EmergencyUnstake,
+
+    #[opcode(91)]
+    #[returns(Vec<u8>)]
+    Sweep,
+
     #[opcode(99)]
     #[returns(String)]
     GetName,
@@ -67,22 +133,39 @@ enum StakingVaultMessage {
 
 impl Token for StakingVault {
     fn name(&self) -> String {
-        format!("{} #{}", COLLECTION_NAME, self.index())
+        let pool_name = POOL_NAME.try_get().ok().flatten();
+        format!("{} #{}", pool_name.as_deref().unwrap_or(FALLBACK_NAME), self.index())
     }
 
     fn symbol(&self) -> String {
-        format!("{} #{}", COLLECTION_SYMBOL, self.index())
+        let pool_symbol = POOL_SYMBOL.try_get().ok().flatten();
+        format!("{} #{}", pool_symbol.as_deref().unwrap_or(FALLBACK_SYMBOL), self.index())
     }
 }
 
 impl StakingVault {
 
-    fn initialize(&self, index: u128) -> Result<CallResponse> {
+    fn initialize(
+        &self,
+        index: u128,
+        staking_token_id: AlkaneId,
+        safety_height: u128,
+    ) -> Result<CallResponse> {
         self.observe_initialization()?;
 
         let context = self.context()?;
-        self.set_collection_alkane_id(&context.caller);
-        self.set_index(index);
+        COLLECTION_ALKANE_ID.set(&context.caller);
+        INDEX.set(&index);
+        STAKING_TOKEN_ID.set(&staking_token_id);
+        let safety_height =
+            u64::try_from(safety_height).map_err(|_| anyhow!("Safety height is out of range"))?;
+        SAFETY_HEIGHT.set(&safety_height);
+        self.record_incoming()?;
+
+        // A pool without a usable name or symbol must not block staking, so
+        // failures leave the cell unset and the fallback is used instead.
+        self.cache_pool_string(&context.caller, POOL_GET_NAME, &POOL_NAME);
+        self.cache_pool_string(&context.caller, POOL_GET_SYMBOL, &POOL_SYMBOL);
 
         let mut response = CallResponse::forward(&AlkaneTransferParcel::default());
         response.alkanes.0.push(AlkaneTransfer {
@@ -92,7 +175,35 @@ impl StakingVault {
         Ok(response)
     }
 
+    // Pool-only: keeps the incoming staking tokens as added principal.
+    fn deposit(&self) -> Result<CallResponse> {
+        let _lock = REENTRANCY_LOCK.enter()?;
+        self.record_incoming()?;
+        self.only_collection()?;
+        Ok(CallResponse::forward(&AlkaneTransferParcel::default()))
+    }
+
+    // Pool-only: sends `amount` of `alkane_id` held by the vault to the pool.
+    fn release(&self, alkane_id: AlkaneId, amount: u128) -> Result<CallResponse> {
+        let _lock = REENTRANCY_LOCK.enter()?;
+        self.record_incoming()?;
+        self.only_collection()?;
+        let context = self.context()?;
+        if self.balance(&context.myself, &alkane_id) < amount {
+            return Err(anyhow!("Vault balance is below the requested amount"));
+        }
+
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+        response.alkanes.0.push(AlkaneTransfer {
+            id: alkane_id,
+            value: amount,
+        });
+        Ok(response)
+    }
+
     fn unstake(&self) -> Result<CallResponse> {
+        let _lock = REENTRANCY_LOCK.enter()?;
+        self.record_incoming()?;
         self.only_owner()?;
         let context = self.context()?;
         if context.incoming_alkanes.0.len() != 1 {
@@ -100,7 +211,7 @@ impl StakingVault {
         }
 
         let mut response = CallResponse::forward(&AlkaneTransferParcel::default());
-        let collection_id = self.collection_ref();
+        let collection_id = self.collection_ref()?;
         let cellpack = Cellpack {
             target: collection_id,
             inputs: vec![51, self.index()],
@@ -111,12 +222,225 @@ impl StakingVault {
             response.alkanes.0.push(*alkane);
         });
 
-        let staking_token_id = AlkaneId::try_from(call_response.data[0..32].to_vec())?;
+        let staking_token_id = Reader::new(&call_response.data).read_alkane_id()?;
+        response.alkanes.0.push(AlkaneTransfer {
+            id: staking_token_id,
+            value: self.balance(&context.myself, &staking_token_id),
+        });
+
+        Ok(response)
+    }
+
+    // Withdraws `amount` of principal through the pool and hands the vault
+    // NFT back, leaving the rest staked.
+    fn partial_unstake(&self, amount: u128) -> Result<CallResponse> {
+        let _lock = REENTRANCY_LOCK.enter()?;
+        self.record_incoming()?;
+        self.only_owner()?;
+        let context = self.context()?;
+
+        // Return the authentication token with the withdrawn principal
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+        let collection_id = self.collection_ref()?;
+        let cellpack = Cellpack {
+            target: collection_id,
+            inputs: vec![52, amount],
+        };
+
+        let call_response = self.call(&cellpack, &AlkaneTransferParcel::default(), self.fuel())?;
+        call_response.alkanes.0.iter().for_each(|alkane| {
+            response.alkanes.0.push(*alkane);
+        });
+
+        let staking_token_id = Reader::new(&call_response.data).read_alkane_id()?;
+        if self.balance(&context.myself, &staking_token_id) < amount {
+            return Err(anyhow!("Vault balance is below the requested amount"));
+        }
+        response.alkanes.0.push(AlkaneTransfer {
+            id: staking_token_id,
+            value: amount,
+        });
+
+        Ok(response)
+    }
+
+    // Moves `amount` of principal into a new vault minted by the pool with a
+    // proportional share of this position's weight. Both NFTs are returned.
+    fn split(&self, amount: u128) -> Result<CallResponse> {
+        let _lock = REENTRANCY_LOCK.enter()?;
+        self.record_incoming()?;
+        self.only_owner()?;
+        let context = self.context()?;
+
+        let staking_token_id = STAKING_TOKEN_ID.require()?;
+        if self.balance(&context.myself, &staking_token_id) < amount {
+            return Err(anyhow!("Vault balance is below the requested amount"));
+        }
+
+        let mut transfer = AlkaneTransferParcel::default();
+        transfer.0.push(AlkaneTransfer {
+            id: staking_token_id,
+            value: amount,
+        });
+        let cellpack = Cellpack {
+            target: self.collection_ref()?,
+            inputs: vec![55, amount],
+        };
+        let call_response = self.call(&cellpack, &transfer, self.fuel())?;
+
+        // Return the authentication token together with the new vault
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+        response.alkanes.0.extend(call_response.alkanes.0);
+        Ok(response)
+    }
+
+    // Starts the pool's cooldown: rewards are paid out now and the NFT is
+    // handed back so it can complete the unstake once unlocked.
+    fn request_unstake(&self) -> Result<CallResponse> {
+        let _lock = REENTRANCY_LOCK.enter()?;
+        self.record_incoming()?;
+        self.only_owner()?;
+        let context = self.context()?;
+
+        // Return the authentication token with the settled rewards
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+        let cellpack = Cellpack {
+            target: self.collection_ref()?,
+            inputs: vec![57],
+        };
+        let call_response = self.call(&cellpack, &AlkaneTransferParcel::default(), self.fuel())?;
+        response.alkanes.0.extend(call_response.alkanes.0);
+        response.data = call_response.data;
+        Ok(response)
+    }
+
+    // Releases the principal after the cooldown; the NFT stays in the vault.
+    fn complete_unstake(&self) -> Result<CallResponse> {
+        let _lock = REENTRANCY_LOCK.enter()?;
+        self.record_incoming()?;
+        self.only_owner()?;
+        let context = self.context()?;
+
+        let mut response = CallResponse::forward(&AlkaneTransferParcel::default());
+        let cellpack = Cellpack {
+            target: self.collection_ref()?,
+            inputs: vec![58],
+        };
+        let call_response = self.call(&cellpack, &AlkaneTransferParcel::default(), self.fuel())?;
+        response.alkanes.0.extend(call_response.alkanes.0);
+
+        let staking_token_id = Reader::new(&call_response.data).read_alkane_id()?;
         response.alkanes.0.push(AlkaneTransfer {
             id: staking_token_id,
             value: self.balance(&context.myself, &staking_token_id),
         });
+        Ok(response)
+    }
+
+    // Moves the whole principal through the pool into `successor`. The new
+    // vault NFT and any settled reward are returned; this vault's NFT stays
+    // behind as its position is closed.
+    fn migrate(&self, successor: AlkaneId) -> Result<CallResponse> {
+        let _lock = REENTRANCY_LOCK.enter()?;
+        self.record_incoming()?;
+        self.only_owner()?;
+        let context = self.context()?;
+
+        let staking_token_id = STAKING_TOKEN_ID.require()?;
+        let mut transfer = AlkaneTransferParcel::default();
+        transfer.0.push(AlkaneTransfer {
+            id: staking_token_id,
+            value: self.balance(&context.myself, &staking_token_id),
+        });
+        let cellpack = Cellpack {
+            target: self.collection_ref()?,
+            inputs: vec![59, successor.block, successor.tx],
+        };
+        let call_response = self.call(&cellpack, &transfer, self.fuel())?;
+
+        let mut response = CallResponse::forward(&AlkaneTransferParcel::default());
+        response.alkanes.0.extend(call_response.alkanes.0);
+        Ok(response)
+    }
+
+    // Turns auto-renew on or off for the position. Once rolled, the new
+    // vault NFT is deposited here and can be taken out with Sweep.
+    fn set_auto_renew(&self, enabled: u128) -> Result<CallResponse> {
+        let _lock = REENTRANCY_LOCK.enter()?;
+        self.record_incoming()?;
+        self.only_owner()?;
+        let context = self.context()?;
+
+        let cellpack = Cellpack {
+            target: self.collection_ref()?,
+            inputs: vec![60, enabled],
+        };
+        self.call(&cellpack, &AlkaneTransferParcel::default(), self.fuel())?;
+
+        // Return the authentication token
+        Ok(CallResponse::forward(&context.incoming_alkanes))
+    }
+
+    // Returns the vault's staking tokens without calling into the pool, for
+    // when the pool cannot be relied on. Open once the safety height is
+    // reached, or while the pool reports itself paused or cancelled. The NFT
+    // stays in the vault, so the position can no longer be used in the pool
+    // and any rewards it had left are forfeited. The pool is not told: its
+    // record of the position stays active as dead weight, and it checks the
+    // vault's balance before moving the principal.
+    fn emergency_unstake(&self) -> Result<CallResponse> {
+        let _lock = REENTRANCY_LOCK.enter()?;
+        self.record_incoming()?;
+        self.only_owner()?;
+        let context = self.context()?;
+
+        if !self.escape_open()? {
+            return Err(anyhow!("Emergency unstake is not available yet"));
+        }
+
+        let staking_token_id = STAKING_TOKEN_ID.require()?;
+        let balance = self.balance(&context.myself, &staking_token_id);
+        if balance == 0 {
+            return Err(anyhow!("Vault holds no staking tokens"));
+        }
+
+        let mut response = CallResponse::forward(&AlkaneTransferParcel::default());
+        response.alkanes.0.push(AlkaneTransfer {
+            id: staking_token_id,
+            value: balance,
+        });
+        Ok(response)
+    }
 
+    // Returns every alkane the vault holds other than the staking token and
+    // its own NFT, together with the NFT used to authenticate.
+    //
+    // Response data, one 48-byte entry per alkane returned:
+    // alkane id (2 × u128 LE) | amount (u128 LE)
+    fn sweep(&self) -> Result<CallResponse> {
+        let _lock = REENTRANCY_LOCK.enter()?;
+        self.only_owner()?;
+        let context = self.context()?;
+        let staking_token_id = STAKING_TOKEN_ID.require()?;
+
+        let mut response = CallResponse::forward(&context.incoming_alkanes);
+        let mut swept = Writer::new();
+        for i in 0..INVENTORY.len()? {
+            let alkane_id = match INVENTORY.get(i)? {
+                Some(id) if id != staking_token_id && id != context.myself => id,
+                _ => continue,
+            };
+            let balance = self.balance(&context.myself, &alkane_id);
+            if balance == 0 {
+                continue;
+            }
+            response.alkanes.0.push(AlkaneTransfer {
+                id: alkane_id,
+                value: balance,
+            });
+            swept.put_alkane_id(&alkane_id).put_u128(balance);
+        }
+        response.data = swept.into_inner();
         Ok(response)
     }
 
@@ -144,7 +468,7 @@ impl StakingVault {
     fn get_collection_identifier(&self) -> Result<CallResponse> {
         let context = self.context()?;
         let mut response = CallResponse::forward(&context.incoming_alkanes);
-        let collection = self.collection_ref();
+        let collection = self.collection_ref()?;
         response.data = format!("{}:{}", collection.block, collection.tx).into_bytes();
         Ok(response)
     }
@@ -160,7 +484,7 @@ impl StakingVault {
         let context = self.context()?;
         let mut response = CallResponse::forward(&context.incoming_alkanes);
 
-        let collection_id = self.collection_ref();
+        let collection_id = self.collection_ref()?;
         let cellpack = Cellpack {
             target: collection_id,
             inputs: vec![1000, self.index()],
@@ -175,7 +499,18 @@ impl StakingVault {
     fn get_content_type(&self) -> Result<CallResponse> {
         let context = self.context()?;
         let mut response = CallResponse::forward(&context.incoming_alkanes);
-        response.data = String::from("image/png").into_bytes().to_vec();
+
+        // The pool serves an SVG card per vault; fall back to the PNG if it
+        // cannot say
+        let cellpack = Cellpack {
+            target: self.collection_ref()?,
+            inputs: vec![1001, self.index()],
+        };
+        response.data =
+            match self.staticcall(&cellpack, &AlkaneTransferParcel::default(), self.fuel()) {
+                Ok(call_response) if !call_response.data.is_empty() => call_response.data,
+                _ => String::from("image/png").into_bytes(),
+            };
         Ok(response)
     }
 
@@ -183,19 +518,88 @@ impl StakingVault {
         let context = self.context()?;
         let mut response = CallResponse::forward(&context.incoming_alkanes);
 
-        let collection_id = self.collection_ref();
-
+        let collection_id = self.collection_ref()?;
         let cellpack = Cellpack {
             target: collection_id,
-            inputs: vec![1002],
+            inputs: vec![POOL_GET_POSITION_BY_VAULT, context.myself.block, context.myself.tx],
         };
-
         let call_response =
             self.staticcall(&cellpack, &AlkaneTransferParcel::default(), self.fuel())?;
-        response.data = call_response.data;
+
+        // Position record layout as documented on the pool's PositionView
+        let mut record = Reader::new(&call_response.data);
+        record.read_alkane_id()?;
+        let amount = record.read_u128()?;
+        let stake_height = record.read_u128()?;
+        let stake_blocks = record.read_u128()?;
+        let weight = record.read_u128()?;
+        let total_reward = record.read_u128()?;
+        let claimed_reward = record.read_u128()?;
+        let state = match record.read_u8()? {
+            0 => "active",
+            1 => "exited",
+            2 => "claimed",
+            3 => "merged",
+            4 => "unbonding",
+            _ => "unknown",
+        };
+        let unlock_height = record.read_u128()?;
+        let auto_renew = record.read_bool()?;
+
+        let metadata = NftMetadata {
+            name: self.name(),
+            description: format!(
+                "Staking position #{} in pool {}:{}.",
+                self.index(),
+                collection_id.block,
+                collection_id.tx
+            ),
+            image: format!("alkane:{}:{}", context.myself.block, context.myself.tx),
+            attributes: vec![
+                Attribute::text("state", state),
+                Attribute::number("stake_block", stake_height as u64),
+                Attribute::amount("stake_amount", amount),
+                Attribute::amount("stake_blocks", stake_blocks),
+                Attribute::amount("weight", weight),
+                Attribute::amount("total_reward", total_reward),
+                Attribute::amount("claimed_reward", claimed_reward),
+                Attribute::number("unlock_height", unlock_height as u64),
+                Attribute::bool("auto_renew", auto_renew),
+            ],
+        };
+        response.data = metadata.to_json().into_bytes();
         Ok(response)
     }
 
+    // Stores the pool's response to `opcode` in `cell` if it is non-empty
+    // UTF-8 within the cell's limit.
+    fn cache_pool_string(&self, pool: &AlkaneId, opcode: u128, cell: &StringCell) {
+        let cellpack = Cellpack {
+            target: *pool,
+            inputs: vec![opcode],
+        };
+        let Ok(call_response) =
+            self.staticcall(&cellpack, &AlkaneTransferParcel::default(), self.fuel())
+        else {
+            return;
+        };
+        let value = String::from_utf8(call_response.data).unwrap_or_default();
+        if !value.is_empty() {
+            let _ = cell.set(&value);
+        }
+    }
+
+    // Adds the ids of the alkanes sent with this call to the inventory.
+    fn record_incoming(&self) -> Result<()> {
+        for alkane in &self.context()?.incoming_alkanes.0 {
+            if IN_INVENTORY.try_get(&alkane.id)?.is_none() {
+                INVENTORY.push(&alkane.id)?;
+                IN_INVENTORY.set(&alkane.id, &true);
+            }
+        }
+        Ok(())
+    }
+
     fn only_owner(&self) -> Result<()> {
         let context = self.context()?;
 
@@ -219,40 +623,41 @@ impl StakingVault {
         Ok(())
     }
 
-    fn set_collection_alkane_id(&self, id: &AlkaneId) {
-        let mut bytes = Vec::with_capacity(32);
-        bytes.extend_from_slice(&id.block.to_le_bytes());
-        bytes.extend_from_slice(&id.tx.to_le_bytes());
-        self.collection_alkane_id_pointer().set(Arc::new(bytes));
-    }
-
-    fn collection_alkane_id_pointer(&self) -> StoragePointer {
-        StoragePointer::from_keyword("/collection-alkane-id")
+    fn only_collection(&self) -> Result<()> {
+        if self.context()?.caller != self.collection_ref()? {
+            return Err(anyhow!("caller is not the staking pool"));
+        }
+        Ok(())
     }
 
-    fn collection_ref(&self) -> AlkaneId {
-        let data = self.collection_alkane_id_pointer().get();
-        if data.len() == 0 {
-            panic!("Collection reference not found");
+    // A pool that errors or traps on the halt query is treated as running;
+    // the safety height still opens the exit.
+    fn escape_open(&self) -> Result<bool> {
+        if let Some(safety_height) = SAFETY_HEIGHT.try_get()? {
+            if self.height() >= safety_height {
+                return Ok(true);
+            }
         }
 
-        let bytes = data.as_ref();
-        AlkaneId {
-            block: u128::from_le_bytes(bytes[0..16].try_into().unwrap()),
-            tx: u128::from_le_bytes(bytes[16..32].try_into().unwrap()),
-        }
+        let cellpack = Cellpack {
+            target: self.collection_ref()?,
+            inputs: vec![POOL_GET_HALT_STATE],
+        };
+        let halted = match self.staticcall(&cellpack, &AlkaneTransferParcel::default(), self.fuel()) {
+            Ok(call_response) => matches!(call_response.data.first(), Some(1) | Some(2)),
+            Err(_) => false,
+        };
+        Ok(halted)
     }
 
-    fn index_pointer(&self) -> StoragePointer {
-        StoragePointer::from_keyword("/index")
+    fn collection_ref(&self) -> Result<AlkaneId> {
+        COLLECTION_ALKANE_ID
+            .try_get()?
+            .ok_or_else(|| anyhow!("Collection reference not found"))
     }
 
     fn index(&self) -> u128 {
-        self.index_pointer().get_value::<u128>()
-    }
-
-    fn set_index(&self, index: u128) {
-        self.index_pointer().set_value::<u128>(index);
+        INDEX.get().unwrap_or_default()
     }
 }
 